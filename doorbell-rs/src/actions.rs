@@ -0,0 +1,45 @@
+use crate::config::ActionConfig;
+use crate::ptz::base64;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const ACTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Outcome of one action request, for the on-screen acknowledgement
+pub(crate) struct ActionResult {
+    pub name: String,
+    pub ok: bool,
+}
+
+// Sends the action's HTTP request on a background thread, so a slow relay
+// never stalls the display, and queues whether it succeeded
+pub(crate) fn trigger(camera: &str, action: &ActionConfig, results: Arc<Mutex<Vec<ActionResult>>>) {
+    let (camera, action) = (camera.to_string(), action.clone());
+    println!("Action: {} on {}", action.name, camera);
+    thread::spawn(move || {
+        let mut request = ureq::request(&action.method, &action.url).timeout(ACTION_TIMEOUT);
+        if let Some(username) = &action.username {
+            let credentials = format!("{}:{}", username, action.password.as_deref().unwrap_or(""));
+            request = request.set("Authorization", &format!("Basic {}", base64(credentials.as_bytes())));
+        }
+        if let Some(content_type) = &action.content_type {
+            request = request.set("Content-Type", content_type);
+        }
+        // Error statuses come back as errors too
+        let result = match &action.body {
+            Some(body) => request.send_string(body),
+            None => request.call(),
+        };
+        let ok = match result {
+            Ok(_) => true,
+            Err(e) => {
+                eprintln!("Action: {} on {} failed: {}", action.name, camera, e);
+                false
+            }
+        };
+        if let Ok(mut q) = results.lock() {
+            q.push(ActionResult { name: action.name, ok });
+        }
+    });
+}
@@ -0,0 +1,181 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Wall-clock steps smaller than this are ordinary drift or loop jitter
+const JUMP_TOLERANCE: Duration = Duration::from_secs(2);
+
+// Source of time for anything tied to the time of day. Timers (dwell,
+// reconnect, heartbeat) stay on `Instant` directly and never see clock steps.
+pub trait Clock {
+    fn wall(&self) -> SystemTime;
+    fn monotonic(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn monotonic(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// Seconds since the epoch; 0 if the clock is set before 1970
+pub fn unix_seconds(clock: &impl Clock) -> u64 {
+    clock.wall().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Minutes since local midnight, using the system timezone
+pub fn local_minute_of_day(clock: &dyn Clock) -> Option<u32> {
+    let secs = clock.wall().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(tm.tm_hour as u32 * 60 + tm.tm_min as u32)
+}
+
+// Local time of day as HH:MM
+pub fn local_hhmm(clock: &dyn Clock) -> String {
+    match local_minute_of_day(clock) {
+        Some(minute) => format!("{:02}:{:02}", minute / 60, minute % 60),
+        None => "--:--".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockJump {
+    Forward(Duration),
+    Backward(Duration),
+}
+
+// Notices the wall clock moving differently from monotonic time, e.g. an NTP
+// step at boot, so wall-clock logic can re-evaluate instead of trusting a
+// huge elapsed value.
+#[derive(Default)]
+pub struct JumpDetector {
+    last: Option<(SystemTime, Instant)>,
+}
+
+impl JumpDetector {
+    pub fn check(&mut self, clock: &impl Clock) -> Option<ClockJump> {
+        let now = (clock.wall(), clock.monotonic());
+        let (last_wall, last_mono) = self.last.replace(now)?;
+
+        let expected = last_wall + now.1.duration_since(last_mono);
+        let jump = match now.0.duration_since(expected) {
+            Ok(ahead) => ClockJump::Forward(ahead),
+            Err(e) => ClockJump::Backward(e.duration()),
+        };
+        let (ClockJump::Forward(size) | ClockJump::Backward(size)) = jump;
+        (size > JUMP_TOLERANCE).then_some(jump)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DndConfig, DndDecode, TimeOfDay};
+    use std::cell::Cell;
+
+    struct FakeClock {
+        wall: Cell<SystemTime>,
+        mono: Cell<Instant>,
+    }
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self { wall: Cell::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), mono: Cell::new(Instant::now()) }
+        }
+
+        fn advance(&self, by: Duration) {
+            self.wall.set(self.wall.get() + by);
+            self.mono.set(self.mono.get() + by);
+        }
+
+        fn step_wall(&self, by: Duration, forward: bool) {
+            let wall = self.wall.get();
+            self.wall.set(if forward { wall + by } else { wall - by });
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn wall(&self) -> SystemTime {
+            self.wall.get()
+        }
+
+        fn monotonic(&self) -> Instant {
+            self.mono.get()
+        }
+    }
+
+    #[test]
+    fn steady_time_is_not_a_jump() {
+        let clock = FakeClock::new();
+        let mut detector = JumpDetector::default();
+        assert_eq!(detector.check(&clock), None);
+        for _ in 0..10 {
+            clock.advance(Duration::from_secs(60));
+            assert_eq!(detector.check(&clock), None);
+        }
+        // Small drift is tolerated
+        clock.step_wall(Duration::from_millis(500), true);
+        assert_eq!(detector.check(&clock), None);
+    }
+
+    #[test]
+    fn detects_steps_in_both_directions() {
+        let clock = FakeClock::new();
+        let mut detector = JumpDetector::default();
+        detector.check(&clock);
+
+        // NTP sync at boot: the clock leaps ahead while barely any real time passes
+        clock.advance(Duration::from_secs(1));
+        clock.step_wall(Duration::from_secs(3600), true);
+        assert_eq!(detector.check(&clock), Some(ClockJump::Forward(Duration::from_secs(3600))));
+
+        clock.advance(Duration::from_secs(1));
+        clock.step_wall(Duration::from_secs(300), false);
+        assert_eq!(detector.check(&clock), Some(ClockJump::Backward(Duration::from_secs(300))));
+
+        // Measured from the new wall time, not the original one
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(detector.check(&clock), None);
+    }
+
+    #[test]
+    fn unix_seconds_clamps_before_epoch() {
+        let clock = FakeClock::new();
+        assert_eq!(unix_seconds(&clock), 1_700_000_000);
+        clock.wall.set(UNIX_EPOCH - Duration::from_secs(10));
+        assert_eq!(unix_seconds(&clock), 0);
+    }
+
+    #[test]
+    fn dnd_follows_a_wall_clock_step() {
+        let clock = FakeClock::new();
+        let minute = local_minute_of_day(&clock).unwrap();
+        // From half an hour to an hour ahead, in local time
+        let window = DndConfig {
+            start: TimeOfDay((minute + 30) % 1440),
+            end: TimeOfDay((minute + 60) % 1440),
+            dpms: false,
+            blank_path: String::new(),
+            wake_seconds: 60,
+            decode: DndDecode::Stop,
+        };
+        let mut detector = JumpDetector::default();
+        detector.check(&clock);
+        assert!(!window.scheduled(&clock));
+
+        clock.step_wall(Duration::from_secs(45 * 60), true);
+        assert!(detector.check(&clock).is_some());
+        assert!(window.scheduled(&clock));
+
+        clock.step_wall(Duration::from_secs(45 * 60), false);
+        assert!(detector.check(&clock).is_some());
+        assert!(!window.scheduled(&clock));
+    }
+}
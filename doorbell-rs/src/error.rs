@@ -0,0 +1,62 @@
+use gstreamer::glib;
+use std::io;
+use thiserror::Error;
+
+/// Errors returned by the library API.
+///
+/// Pipeline errors carry the camera name so callers can treat a single bad
+/// camera differently from a fatal framebuffer or config problem.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+
+    #[error("framebuffer {path}: {message}")]
+    Framebuffer { path: String, message: String },
+
+    #[error("camera {camera}: {message}")]
+    Pipeline { camera: String, message: String },
+
+    // Elements the pipeline description names but GStreamer doesn't have
+    #[error("camera {camera}: missing GStreamer element(s) {}", elements.join(", "))]
+    MissingElements { camera: String, elements: Vec<String> },
+
+    #[error("input: {0}")]
+    Input(String),
+
+    #[error("gstreamer: {0}")]
+    Gstreamer(String),
+
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    // For use with `map_err`, e.g. `.map_err(Error::io("Could not bind socket"))`
+    pub(crate) fn io(context: impl Into<String>) -> impl FnOnce(io::Error) -> Error {
+        let context = context.into();
+        move |source| Error::Io { context, source }
+    }
+
+    pub(crate) fn pipeline(camera: &str, message: impl ToString) -> Error {
+        Error::Pipeline { camera: camera.to_string(), message: message.to_string() }
+    }
+}
+
+impl From<glib::Error> for Error {
+    fn from(e: glib::Error) -> Self {
+        Error::Gstreamer(e.to_string())
+    }
+}
+
+impl From<glib::BoolError> for Error {
+    fn from(e: glib::BoolError) -> Self {
+        Error::Gstreamer(e.to_string())
+    }
+}
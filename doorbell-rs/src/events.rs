@@ -0,0 +1,180 @@
+use crate::clock::{unix_seconds, SystemClock};
+use crate::error::{Error, Result};
+use crate::worker::{sleep_while, Worker};
+use serde::Serialize;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixListener;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const EVENT_CLIENT_BUFFER: usize = 32; // events queued per socket client before dropping
+const ACCEPT_POLL: Duration = Duration::from_millis(100); // between checks for new clients
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchReason {
+    Touch,
+    AutoCycle,
+    Doorbell,
+    // Back to the camera that was showing before a doorbell press
+    Resume,
+    // Back to home_camera after nobody touched the screen for a while
+    Home,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Switched { camera: &'a str, index: usize, reason: SwitchReason },
+    PipelineError { camera: &'a str, message: String },
+    // Do-not-disturb started (display off) or ended
+    Dnd { active: bool },
+}
+
+#[derive(Serialize)]
+struct EventEnvelope<'a> {
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+// Fans events out to connected socket clients. Each client has its own writer
+// thread and bounded queue, so a stalled client only loses its own events.
+// Dropping the bus stops listening, disconnects every client and removes the
+// socket, so the next run can bind the same path.
+#[derive(Default)]
+pub struct EventBus {
+    clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>>,
+    listener: Mutex<Option<Worker>>,
+}
+
+impl EventBus {
+    pub fn listen(&self, path: &str) -> Result<()> {
+        // Remove a stale socket left behind by a previous run, but nothing else:
+        // a mistyped path must not cost someone a file
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(Error::Config(format!("event_socket {} exists and is not a socket", path)));
+            }
+            fs::remove_file(path).map_err(Error::io(format!("Could not remove stale socket {}", path)))?;
+        }
+        let listener = UnixListener::bind(path)
+            .map_err(Error::io(format!("Could not bind event socket {}", path)))?;
+        // Polled, so the thread can see it should stop
+        listener.set_nonblocking(true).map_err(Error::io(format!("Could not set up event socket {}", path)))?;
+        println!("Events: Listening on {}", path);
+
+        let clients = self.clients.clone();
+        let path = path.to_string();
+        let worker = Worker::spawn(move |running| {
+            while running.load(Ordering::Relaxed) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        sleep_while(running, ACCEPT_POLL);
+                        continue;
+                    }
+                    Err(e) => {
+                        eprintln!("Events: Accept failed: {}", e);
+                        sleep_while(running, ACCEPT_POLL);
+                        continue;
+                    }
+                };
+                if let Err(e) = stream.set_nonblocking(false) {
+                    eprintln!("Events: Could not set up client: {}", e);
+                    continue;
+                }
+                let (tx, rx) = mpsc::sync_channel::<Arc<str>>(EVENT_CLIENT_BUFFER);
+                thread::spawn(move || {
+                    for line in rx {
+                        if stream.write_all(line.as_bytes()).is_err() {
+                            break; // Client went away; dropping rx unregisters it
+                        }
+                    }
+                });
+                if let Ok(mut c) = clients.lock() {
+                    c.push(tx);
+                }
+            }
+            // Dropping the senders ends each writer thread, which closes its client
+            if let Ok(mut c) = clients.lock() {
+                c.clear();
+            }
+            drop(listener);
+            fs::remove_file(&path).ok();
+        });
+        if let Ok(mut slot) = self.listener.lock() {
+            *slot = Some(worker);
+        }
+        Ok(())
+    }
+
+    pub fn emit(&self, event: Event) {
+        let Ok(mut clients) = self.clients.lock() else { return };
+        if clients.is_empty() {
+            return;
+        }
+
+        let timestamp = unix_seconds(&SystemClock);
+        let mut line = match serde_json::to_string(&EventEnvelope { timestamp, event: &event }) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Events: Could not serialize event: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+        let line: Arc<str> = line.into();
+
+        clients.retain(|tx| match tx.try_send(line.clone()) {
+            Ok(()) | Err(TrySendError::Full(_)) => true,
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_files_that_are_not_sockets_alone() {
+        let path = std::env::temp_dir().join(format!("doorbell-rs-events-{}.txt", std::process::id()));
+        fs::write(&path, "notes").unwrap();
+        let path_str = path.to_str().unwrap();
+        let err = EventBus::default().listen(path_str).unwrap_err().to_string();
+        assert!(err.contains("not a socket"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "notes");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn dropping_the_bus_disconnects_clients_and_frees_the_path() {
+        use std::io::Read;
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir().join(format!("doorbell-rs-events-{}.sock", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        let bus = EventBus::default();
+        bus.listen(path_str).unwrap();
+        let mut client = UnixStream::connect(&path).unwrap();
+        // Registered once the accept loop has seen it
+        while bus.clients.lock().unwrap().is_empty() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        drop(bus);
+
+        let mut rest = String::new();
+        client.read_to_string(&mut rest).unwrap();
+        assert!(rest.is_empty());
+        assert!(!path.exists());
+        // The same path binds again, as after a reload
+        let bus = EventBus::default();
+        bus.listen(path_str).unwrap();
+    }
+}
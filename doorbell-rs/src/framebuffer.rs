@@ -0,0 +1,367 @@
+use crate::error::{Error, Result};
+use crate::{FRAME_HEIGHT, FRAME_WIDTH};
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use gstreamer_video::VideoFormat;
+use std::fs::{self, File};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+const FBIOGET_FSCREENINFO: libc::c_ulong = 0x4602;
+
+// struct fb_fix_screeninfo from <linux/fb.h>
+#[repr(C)]
+struct FixScreenInfo {
+    id: [u8; 16],
+    smem_start: libc::c_ulong,
+    smem_len: u32,
+    kind: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    line_length: u32,
+    mmio_start: libc::c_ulong,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+}
+
+// Bytes per scanline as the driver reports it, which can include padding
+fn line_length(file: &File) -> Option<u32> {
+    let mut info: FixScreenInfo = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), FBIOGET_FSCREENINFO as _, &mut info) };
+    (result == 0 && info.line_length > 0).then_some(info.line_length)
+}
+
+pub struct Framebuffer {
+    pub mem: memmap2::MmapMut,
+    pub width: u32,
+    pub height: u32,
+    pub stride: u32,
+}
+
+// Geometry and pixel format of a frame, either the framebuffer (always BGRA) or a decoded sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLayout {
+    pub width: u32,
+    pub height: u32,
+    pub stride: u32,
+    pub format: VideoFormat,
+}
+
+impl FrameLayout {
+    pub fn from_caps(caps: &gstreamer::CapsRef) -> Option<Self> {
+        let info = gstreamer_video::VideoInfo::from_caps(caps).ok()?;
+        Some(Self { width: info.width(), height: info.height(), stride: info.stride()[0] as u32, format: info.format() })
+    }
+
+    // Why a frame with this layout and `len` bytes can't be blitted to `expected`, if it can't
+    pub fn mismatch(&self, expected: &FrameLayout, len: usize) -> Option<String> {
+        // Checked first: stride and length mean nothing in another format
+        if self.format != expected.format {
+            return Some(format!("frame format is {}, expected {}", self.format.to_str(), expected.format.to_str()));
+        }
+        if self.width != expected.width || self.height != expected.height {
+            return Some(format!("frame is {}x{}, expected {}x{}", self.width, self.height, expected.width, expected.height));
+        }
+        if self.stride != expected.stride {
+            return Some(format!("frame stride is {} bytes, expected {}", self.stride, expected.stride));
+        }
+        let needed = self.stride as usize * self.height as usize;
+        if len < needed {
+            return Some(format!("buffer is {} bytes, expected {}", len, needed));
+        }
+        None
+    }
+}
+
+impl Framebuffer {
+    pub fn new(path: &str) -> Result<Self> {
+        let fb_error = |message: String| Error::Framebuffer { path: path.to_string(), message };
+
+        let file = File::options().read(true).write(true).open(path)
+            .map_err(|e| fb_error(format!("Failed to open framebuffer device: {}", e)))?;
+
+        // In production, use `ioctl` (FBIOGET_VSCREENINFO) to populate these.
+        // For Pi Touchscreen, 800x480 is standard.
+        let width = FRAME_WIDTH;
+        let height = FRAME_HEIGHT;
+        let bpp = 32;
+        // Not a framebuffer device (e.g. a plain file in tests): assume unpadded lines
+        let stride = match line_length(&file) {
+            Some(stride) if stride >= width * (bpp / 8) => stride,
+            Some(stride) => return Err(fb_error(format!("Line length {} is too short for {} pixels at {} bpp", stride, width, bpp))),
+            None => width * (bpp / 8),
+        };
+        if stride != width * (bpp / 8) {
+            println!("Framebuffer: {} bytes per line, {} of them padding", stride, stride - width * (bpp / 8));
+        }
+
+        let mem = unsafe { memmap2::MmapMut::map_mut(&file) }
+            .map_err(|e| fb_error(format!("Failed to map framebuffer: {}", e)))?;
+
+        // Some headless setups expose an empty or short mapping; drawing into it shows nothing
+        let needed = stride as usize * height as usize;
+        if mem.len() < needed {
+            return Err(fb_error(format!(
+                "Mapped {} bytes, expected at least {} for {}x{} at {} bpp",
+                mem.len(), needed, width, height, bpp
+            )));
+        }
+
+        Ok(Self { mem, width, height, stride })
+    }
+
+    pub fn layout(&self) -> FrameLayout {
+        FrameLayout { width: self.width, height: self.height, stride: self.stride, format: VideoFormat::Bgra }
+    }
+
+    // Draw target that blends everything drawn through it at a fixed alpha
+    pub fn blended(&mut self, alpha: u8) -> Blended<'_> {
+        Blended { fb: self, alpha }
+    }
+
+    // Copies a frame in with its first row at line `top`
+    pub fn blit(&mut self, frame: &[u8], layout: FrameLayout, top: u32) {
+        self.blit_rows(frame, layout, Point::new(0, top as i32), |dst, src| dst.copy_from_slice(src));
+    }
+
+    // Copies a smaller frame in with its top-left corner at `origin`
+    pub fn blit_at(&mut self, frame: &[u8], layout: FrameLayout, origin: Point) {
+        self.blit_rows(frame, layout, origin, |dst, src| dst.copy_from_slice(src));
+    }
+
+    // As `blit`, each channel scaled by `brightness`/255
+    pub fn blit_dimmed(&mut self, frame: &[u8], layout: FrameLayout, top: u32, brightness: u8) {
+        self.blit_rows(frame, layout, Point::new(0, top as i32), |dst, src| {
+            for (d, &s) in dst.iter_mut().zip(src) {
+                *d = (s as u16 * brightness as u16 / 255) as u8;
+            }
+        });
+    }
+
+    // Row by row, since both the frame and the device lines may be padded past the pixels.
+    // Whatever falls outside the visible screen is clipped, never wrapped or
+    // written into the rest of the mapping (e.g. a virtual resolution's spare lines).
+    fn blit_rows(&mut self, frame: &[u8], layout: FrameLayout, origin: Point, mut copy: impl FnMut(&mut [u8], &[u8])) {
+        // Columns and rows of the frame that are left of or above the screen
+        let (skip_x, skip_y) = (origin.x.min(0).unsigned_abs(), origin.y.min(0).unsigned_abs());
+        let (left, top) = (origin.x.max(0) as u32, origin.y.max(0) as u32);
+        let columns = layout.width.saturating_sub(skip_x).min(self.width.saturating_sub(left));
+        let rows = layout.height.saturating_sub(skip_y).min(self.height.saturating_sub(top));
+        let (skip_bytes, row_bytes) = (skip_x as usize * 4, columns as usize * 4);
+        let lines = frame.chunks(layout.stride as usize).skip(skip_y as usize).take(rows as usize);
+        for (y, src) in lines.enumerate() {
+            let Some(src) = src.get(skip_bytes..(skip_bytes + row_bytes).min(src.len())) else { break };
+            let start = (top as usize + y) * self.stride as usize + left as usize * 4;
+            let Some(dst) = self.mem.get_mut(start..start + src.len()) else { break };
+            copy(dst, src);
+        }
+    }
+
+    pub fn blend(&mut self, x: i32, y: i32, color: Rgb888, alpha: u8) {
+        if alpha == 0 || x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
+            return;
+        }
+        let offset = (y as usize * self.stride as usize) + (x as usize * 4);
+        if offset + 4 <= self.mem.len() {
+            blend_pixel(&mut self.mem[offset..offset + 4], color, alpha);
+        }
+    }
+}
+
+// Powers the panel down (or back up) through its sysfs blank node
+pub fn set_blank(path: &str, blank: bool) {
+    if let Err(e) = fs::write(path, if blank { "1" } else { "0" }) {
+        eprintln!("Framebuffer: Could not {} via {}: {}", if blank { "blank" } else { "unblank" }, path, e);
+    }
+}
+
+// Keeps the panel awake by unblanking it at a fixed interval
+pub struct Unblanker {
+    path: String,
+    interval: Duration,
+    last: Option<Instant>,
+    failing: bool,
+}
+
+impl Unblanker {
+    pub fn new(path: &str, interval: Duration) -> Self {
+        Self { path: path.to_string(), interval, last: None, failing: false }
+    }
+
+    pub fn tick(&mut self) {
+        if self.last.is_some_and(|t| t.elapsed() < self.interval) {
+            return;
+        }
+        self.last = Some(Instant::now());
+        match fs::write(&self.path, "0") {
+            Ok(()) => {
+                if self.failing {
+                    println!("Framebuffer: Unblanking {} works again", self.path);
+                }
+                self.failing = false;
+            }
+            // Logged once per failure streak rather than every interval
+            Err(e) if !self.failing => {
+                eprintln!("Framebuffer: Could not unblank via {}: {}", self.path, e);
+                self.failing = true;
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+impl DrawTarget for Framebuffer {
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if coord.x >= 0 && coord.x < self.width as i32 && coord.y >= 0 && coord.y < self.height as i32 {
+                let offset = (coord.y as usize * self.stride as usize) + (coord.x as usize * 4);
+                if offset + 4 <= self.mem.len() {
+                    // BGRA
+                    self.mem[offset] = color.b();
+                    self.mem[offset + 1] = color.g();
+                    self.mem[offset + 2] = color.r();
+                    self.mem[offset + 3] = 255;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Framebuffer {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+// Blends `color` with coverage `alpha` over a BGRA pixel
+pub fn blend_pixel(dst: &mut [u8], color: Rgb888, alpha: u8) {
+    let a = alpha as u32;
+    for (d, s) in dst.iter_mut().zip([color.b(), color.g(), color.r()]) {
+        *d = ((s as u32 * a + *d as u32 * (255 - a)) / 255) as u8;
+    }
+    dst[3] = 255;
+}
+
+pub struct Blended<'a> {
+    fb: &'a mut Framebuffer,
+    alpha: u8,
+}
+
+impl DrawTarget for Blended<'_> {
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            self.fb.blend(coord.x, coord.y, color, self.alpha);
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Blended<'_> {
+    fn size(&self) -> Size {
+        self.fb.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPECTED: FrameLayout = FrameLayout { width: 800, height: 480, stride: 3200, format: VideoFormat::Bgra };
+
+    #[test]
+    fn matching_layout_is_accepted() {
+        assert_eq!(EXPECTED.mismatch(&EXPECTED, 3200 * 480), None);
+        // Trailing bytes beyond the last row are harmless
+        assert_eq!(EXPECTED.mismatch(&EXPECTED, 3200 * 480 + 64), None);
+    }
+
+    #[test]
+    fn detects_dimension_stride_and_length_mismatches() {
+        let small = FrameLayout { width: 640, height: 480, stride: 2560, ..EXPECTED };
+        assert!(small.mismatch(&EXPECTED, 2560 * 480).unwrap().contains("640x480"));
+
+        let padded = FrameLayout { stride: 3328, ..EXPECTED };
+        assert!(padded.mismatch(&EXPECTED, 3328 * 480).unwrap().contains("stride"));
+
+        assert!(EXPECTED.mismatch(&EXPECTED, 3200 * 479).unwrap().contains("buffer"));
+    }
+
+    #[test]
+    fn blits_rows_onto_padded_device_lines() {
+        // 4x3 device with 8 bytes of padding per line
+        let mut fb = Framebuffer { mem: memmap2::MmapMut::map_anon(24 * 3).unwrap(), width: 4, height: 3, stride: 24 };
+        fb.mem.fill(0xEE);
+        let layout = FrameLayout { width: 4, height: 2, stride: 16, format: VideoFormat::Bgra };
+        let frame: Vec<u8> = (0..32).collect();
+
+        fb.blit(&frame, layout, 1);
+        // Line 0 is above `top`; padding bytes are never written
+        assert!(fb.mem[..24].iter().all(|&b| b == 0xEE));
+        assert_eq!(&fb.mem[24..40], &frame[..16]);
+        assert!(fb.mem[40..48].iter().all(|&b| b == 0xEE));
+        assert_eq!(&fb.mem[48..64], &frame[16..]);
+        assert!(fb.mem[64..72].iter().all(|&b| b == 0xEE));
+
+        fb.blit_dimmed(&frame, layout, 1, 128);
+        // 31 * 128 / 255
+        assert_eq!(fb.mem[48 + 15], 15);
+    }
+
+    #[test]
+    fn clips_a_frame_hanging_off_the_screen() {
+        // 4x3 visible, with a spare virtual line after it
+        let mut fb = Framebuffer { mem: memmap2::MmapMut::map_anon(16 * 4).unwrap(), width: 4, height: 3, stride: 16 };
+        fb.mem.fill(0xEE);
+        // 5x3 frame, pixel value = its index
+        let layout = FrameLayout { width: 5, height: 3, stride: 20, format: VideoFormat::Bgra };
+        let frame: Vec<u8> = (0..15).flat_map(|i| [i; 4]).collect();
+        let pixel = |fb: &Framebuffer, x: usize, y: usize| fb.mem[y * 16 + x * 4];
+
+        // Two rows off the bottom and a column off the right: the spare line stays untouched
+        fb.blit(&frame, layout, 2);
+        assert_eq!([pixel(&fb, 0, 2), pixel(&fb, 3, 2)], [0, 3]);
+        assert!(fb.mem[..32].iter().all(|&b| b == 0xEE));
+        assert!(fb.mem[48..].iter().all(|&b| b == 0xEE));
+
+        // One column and one row off the top left: pixel 6 lands at 0,0
+        fb.mem.fill(0xEE);
+        fb.blit_at(&frame, layout, Point::new(-1, -1));
+        assert_eq!([pixel(&fb, 0, 0), pixel(&fb, 3, 0), pixel(&fb, 0, 1), pixel(&fb, 3, 1)], [6, 9, 11, 14]);
+        assert_eq!(pixel(&fb, 0, 2), 0xEE);
+    }
+
+    #[test]
+    fn rejects_samples_that_are_not_bgra() {
+        gstreamer::init().unwrap();
+        let caps = gstreamer::Caps::builder("video/x-raw")
+            .field("format", "NV12")
+            .field("width", 800i32)
+            .field("height", 480i32)
+            .field("framerate", gstreamer::Fraction::new(30, 1))
+            .build();
+        let sample = gstreamer::Sample::builder().caps(&caps).build();
+
+        let layout = FrameLayout::from_caps(sample.caps().unwrap()).unwrap();
+        assert_eq!(layout.format, VideoFormat::Nv12);
+        // Same size and a plausible length, but the bytes aren't BGRA
+        let reason = layout.mismatch(&EXPECTED, 3200 * 480).unwrap();
+        assert_eq!(reason, "frame format is NV12, expected BGRA");
+    }
+}
@@ -0,0 +1,107 @@
+use crate::config::FreezeConfig;
+use crate::framebuffer::FrameLayout;
+use std::time::{Duration, Instant};
+
+// Sample grid laid over each frame; enough to see any real scene change
+const GRID_COLUMNS: u32 = 64;
+const GRID_ROWS: u32 = 36;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FreezeChange {
+    // Unchanged for the configured time
+    Frozen(Duration),
+    // Moving again after a freeze
+    Thawed,
+}
+
+// Notices a stream that keeps delivering the same picture, e.g. a hung
+// encoder, which the pipeline itself sees as perfectly healthy. Frames are
+// compared on a coarse grid of luma samples; a frame counts as changed once
+// any sample moves by more than `threshold`.
+pub(crate) struct FreezeDetector {
+    after: Duration,
+    threshold: u8,
+    samples: Vec<u8>,
+    previous: Vec<u8>,
+    unchanged_since: Option<Instant>,
+    frozen: bool,
+}
+
+impl FreezeDetector {
+    pub fn new(config: &FreezeConfig) -> Self {
+        Self {
+            after: Duration::from_secs(config.seconds),
+            threshold: config.threshold,
+            samples: Vec::with_capacity((GRID_COLUMNS * GRID_ROWS) as usize),
+            previous: Vec::new(),
+            unchanged_since: None,
+            frozen: false,
+        }
+    }
+
+    // Forgets the previous frame, e.g. for a new pipeline
+    pub fn reset(&mut self) {
+        self.previous.clear();
+        self.unchanged_since = None;
+        self.frozen = false;
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    pub fn check(&mut self, layout: FrameLayout, bgra: &[u8], now: Instant) -> Option<FreezeChange> {
+        self.samples.clear();
+        for gy in 0..GRID_ROWS {
+            let row = ((2 * gy + 1) * layout.height / (2 * GRID_ROWS)) as usize * layout.stride as usize;
+            for gx in 0..GRID_COLUMNS {
+                let i = row + ((2 * gx + 1) * layout.width / (2 * GRID_COLUMNS)) as usize * 4;
+                let luma = bgra.get(i..i + 3).map_or(0, |px| ((px[0] as u16 + 2 * px[1] as u16 + px[2] as u16) / 4) as u8);
+                self.samples.push(luma);
+            }
+        }
+        let changed = self.previous.len() != self.samples.len()
+            || self.samples.iter().zip(&self.previous).any(|(a, b)| a.abs_diff(*b) > self.threshold);
+        std::mem::swap(&mut self.samples, &mut self.previous);
+
+        if changed {
+            self.unchanged_since = Some(now);
+            return std::mem::take(&mut self.frozen).then_some(FreezeChange::Thawed);
+        }
+        let unchanged = now.saturating_duration_since(*self.unchanged_since.get_or_insert(now));
+        if !self.frozen && unchanged >= self.after {
+            self.frozen = true;
+            return Some(FreezeChange::Frozen(unchanged));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gstreamer_video::VideoFormat;
+
+    #[test]
+    fn flags_a_picture_that_stops_changing_and_when_it_moves_again() {
+        let layout = FrameLayout { width: 160, height: 90, stride: 640, format: VideoFormat::Bgra };
+        let config = FreezeConfig { seconds: 10, threshold: 8, restart: false };
+        let mut detector = FreezeDetector::new(&config);
+        let mut frame = vec![100u8; 640 * 90];
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(detector.check(layout, &frame, at(0)), None);
+        assert_eq!(detector.check(layout, &frame, at(9)), None);
+        // Noise within the threshold still counts as the same picture
+        frame[640 * 46 + 4 * 81 + 1] = 104;
+        assert_eq!(detector.check(layout, &frame, at(10)), Some(FreezeChange::Frozen(Duration::from_secs(10))));
+        assert!(detector.frozen());
+        assert_eq!(detector.check(layout, &frame, at(20)), None);
+
+        frame.iter_mut().for_each(|b| *b = 200);
+        assert_eq!(detector.check(layout, &frame, at(21)), Some(FreezeChange::Thawed));
+        assert!(!detector.frozen());
+        assert_eq!(detector.check(layout, &frame, at(30)), None);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::clock::{unix_seconds, SystemClock};
+use crate::config::HealthLogConfig;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum HealthEvent {
+    // Pipeline opened, on startup or a switch
+    Connecting,
+    // First frame after connecting or reconnecting
+    Connected,
+    Error { message: String },
+    // Frames still arriving, but the same picture for `seconds`
+    Frozen { seconds: u64 },
+    Reconnecting,
+}
+
+#[derive(Serialize)]
+struct HealthRecord<'a> {
+    timestamp: u64,
+    camera: &'a str,
+    #[serde(flatten)]
+    event: &'a HealthEvent,
+}
+
+// Appends camera lifecycle events to a JSON-lines file, moving it to
+// `<path>.1` once it reaches the size limit. The file is opened per record,
+// so several displays can share it and a rotated file is never held open.
+pub(crate) struct HealthLog<'a> {
+    config: &'a HealthLogConfig,
+}
+
+impl<'a> HealthLog<'a> {
+    pub fn new(config: &'a HealthLogConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn record(&self, camera: &str, event: HealthEvent) {
+        let record = HealthRecord { timestamp: unix_seconds(&SystemClock), camera, event: &event };
+        let mut line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Health: Could not serialize event: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+
+        let path = &self.config.path;
+        if fs::metadata(path).is_ok_and(|m| m.len() + line.len() as u64 > self.config.max_bytes) {
+            if let Err(e) = fs::rename(path, format!("{}.1", path)) {
+                eprintln!("Health: Could not rotate {}: {}", path, e);
+            }
+        }
+        let written = OpenOptions::new().create(true).append(true).open(path).and_then(|mut f| f.write_all(line.as_bytes()));
+        if let Err(e) = written {
+            eprintln!("Health: Could not write {}: {}", path, e);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::net::Ipv4Addr;
+use std::ptr;
+
+// IPv4 addresses of the interfaces that are up, loopback excluded
+pub(crate) fn local_addresses() -> Vec<Ipv4Addr> {
+    let mut addresses = Vec::new();
+    let mut ifaddrs: *mut libc::ifaddrs = ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return addresses;
+    }
+
+    let mut cursor = ifaddrs;
+    while let Some(ifa) = unsafe { cursor.as_ref() } {
+        cursor = ifa.ifa_next;
+        let up = ifa.ifa_flags & libc::IFF_UP as u32 != 0;
+        let loopback = ifa.ifa_flags & libc::IFF_LOOPBACK as u32 != 0;
+        let Some(addr) = (unsafe { ifa.ifa_addr.as_ref() }) else { continue };
+        if !up || loopback || addr.sa_family as i32 != libc::AF_INET {
+            continue;
+        }
+        let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+        addresses.push(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)));
+    }
+    unsafe { libc::freeifaddrs(ifaddrs) };
+    addresses
+}
+
+// Setup instructions with {ip} and {config} filled in
+pub(crate) fn setup_text(template: &str, config_path: &str) -> String {
+    let addresses: Vec<_> = local_addresses().iter().map(Ipv4Addr::to_string).collect();
+    let ip = if addresses.is_empty() { "no network yet".to_string() } else { addresses.join(", ") };
+    template.replace("{ip}", &ip).replace("{config}", config_path)
+}
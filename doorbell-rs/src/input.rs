@@ -0,0 +1,272 @@
+use crate::worker::{sleep_while, Worker};
+use crate::{FRAME_HEIGHT, FRAME_WIDTH};
+use embedded_graphics::prelude::Point;
+use evdev::{AbsoluteAxisType, Device, InputEventKind, Key};
+use std::fs;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const GPIO_POLL_MS: u64 = 20; // short enough to catch a quick button press
+const TOUCH_RETRY: Duration = Duration::from_secs(10); // rescan for hotplugged touchscreens
+const INPUT_POLL: Duration = Duration::from_millis(200); // how soon the thread notices it should stop
+const SWIPE_DISTANCE: i32 = 80; // pixels moved vertically before a touch counts as a swipe
+
+// First input device that looks like a touchscreen
+pub(crate) fn find_touch_device() -> Option<PathBuf> {
+    let dir = fs::read_dir("/dev/input").ok()?;
+    for entry in dir.flatten() {
+        if let Ok(dev) = Device::open(entry.path()) {
+            let name = dev.name().unwrap_or("").to_lowercase();
+            if name.contains("touch") || name.contains("ads7846") || name.contains("waveshare") {
+                println!("Input: Found {}", name);
+                return Some(entry.path());
+            }
+        }
+    }
+    None
+}
+
+// `device` if it exists, or else the first touchscreen found
+pub(crate) fn touch_device(device: Option<&Path>) -> Option<PathBuf> {
+    match device {
+        Some(path) => path.exists().then(|| path.to_path_buf()),
+        None => find_touch_device(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Gesture {
+    Tap,
+    // Held for at least the long-press time
+    LongPress,
+    // Dragged up or down, mostly vertically
+    SwipeUp,
+    SwipeDown,
+}
+
+// One touch, queued on release
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tap {
+    // None when the device has no absolute axes; where the touch was lifted
+    pub point: Option<Point>,
+    pub gesture: Gesture,
+}
+
+// A swipe wins over a long press, since dragging takes time too
+fn classify(start: Option<Point>, end: Option<Point>, held: Duration, long_press: Option<Duration>) -> Gesture {
+    if let (Some(start), Some(end)) = (start, end) {
+        let (dx, dy) = (end.x - start.x, start.y - end.y);
+        if dy.abs() >= SWIPE_DISTANCE && dy.abs() > 2 * dx.abs() {
+            return if dy > 0 { Gesture::SwipeUp } else { Gesture::SwipeDown };
+        }
+    }
+    if long_press.is_some_and(|min| held >= min) {
+        Gesture::LongPress
+    } else {
+        Gesture::Tap
+    }
+}
+
+// Maps raw axis values onto screen pixels using the device's reported ranges
+struct AxisScale {
+    x: (i32, i32),
+    y: (i32, i32),
+}
+
+impl AxisScale {
+    fn from_device(dev: &Device) -> Option<Self> {
+        let abs = dev.get_abs_state().ok()?;
+        let range = |axis: AbsoluteAxisType| {
+            let info = abs[axis.0 as usize];
+            (info.maximum > info.minimum).then_some((info.minimum, info.maximum))
+        };
+        Some(Self { x: range(AbsoluteAxisType::ABS_X)?, y: range(AbsoluteAxisType::ABS_Y)? })
+    }
+
+    fn to_screen(&self, x: i32, y: i32) -> Point {
+        let scale = |v: i32, (min, max): (i32, i32), size: u32| {
+            ((v - min) as i64 * (size as i64 - 1) / (max - min) as i64) as i32
+        };
+        Point::new(scale(x, self.x, FRAME_WIDTH), scale(y, self.y, FRAME_HEIGHT))
+    }
+}
+
+// Waits up to `timeout` for events; errors count as ready so fetch_events reports them
+fn wait_readable(dev: &Device, timeout: Duration) -> bool {
+    let mut fd = libc::pollfd { fd: dev.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+    let ready = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) };
+    ready != 0
+}
+
+// Queues a tap per touch release, with its screen position when the device
+// reports absolute axes. Reads `device`, or else the first touchscreen found,
+// and keeps looking for it while none is present (or after it disappears),
+// with `available` tracking which it is. With `debug` every raw event is
+// logged, for working out a panel's event codes. Without `long_press` no
+// touch is ever a long press. With `grab` the app takes the device for
+// itself, so touches don't also move the console's pointer; the kernel drops
+// a grab when the process exits, however it exits. Dropping the returned
+// worker waits until the device is released.
+pub(crate) fn spawn_input_thread(
+    device: Option<PathBuf>,
+    events: Arc<Mutex<Vec<Tap>>>,
+    available: Arc<AtomicBool>,
+    debug: bool,
+    long_press: Option<Duration>,
+    grab: bool,
+) -> Worker {
+    Worker::spawn(move |running| {
+        let mut warned = false;
+        while running.load(Ordering::Relaxed) {
+            let Some(mut dev) = touch_device(device.as_deref()).and_then(|path| Device::open(path).ok()) else {
+                if !warned {
+                    eprintln!("Input: No touch device found, retrying every {}s", TOUCH_RETRY.as_secs());
+                    warned = true;
+                }
+                sleep_while(running, TOUCH_RETRY);
+                continue;
+            };
+            available.store(true, Ordering::Relaxed);
+            warned = false;
+            let grabbed = grab && match dev.grab() {
+                Ok(()) => {
+                    println!("Input: Grabbed touch device for exclusive use");
+                    true
+                }
+                Err(e) => {
+                    eprintln!("Input: Could not grab touch device, touches also reach other readers: {}", e);
+                    false
+                }
+            };
+            let scale = AxisScale::from_device(&dev);
+            let (mut x, mut y) = (0, 0);
+            let mut pressed_at = None;
+            // Position after the first complete report of a touch
+            let mut start = None;
+            let mut awaiting_start = false;
+
+            // Polled first, since fetch_events blocks and would never see `running` clear
+            let mut lost = false;
+            while running.load(Ordering::Relaxed) {
+                if !wait_readable(&dev, INPUT_POLL) {
+                    continue;
+                }
+                let Ok(iter) = dev.fetch_events() else {
+                    lost = true;
+                    break;
+                };
+                for ev in iter {
+                    if debug {
+                        println!("Input: {:?} type={} code={} value={}", ev.kind(), ev.event_type().0, ev.code(), ev.value());
+                    }
+                    match ev.kind() {
+                        InputEventKind::AbsAxis(AbsoluteAxisType::ABS_X) => x = ev.value(),
+                        InputEventKind::AbsAxis(AbsoluteAxisType::ABS_Y) => y = ev.value(),
+                        InputEventKind::Synchronization(_) if awaiting_start => {
+                            awaiting_start = false;
+                            start = scale.as_ref().map(|s| s.to_screen(x, y));
+                        }
+                        InputEventKind::Key(Key::BTN_TOUCH) => match ev.value() {
+                            1 => {
+                                pressed_at = Some(Instant::now());
+                                awaiting_start = true;
+                            }
+                            0 => {
+                                // Touch Release -> Click
+                                let point = scale.as_ref().map(|s| s.to_screen(x, y));
+                                let held = pressed_at.take().map_or(Duration::ZERO, |at| at.elapsed());
+                                let gesture = classify(start.take(), point, held, long_press);
+                                if let Ok(mut q) = events.lock() {
+                                    q.push(Tap { point, gesture });
+                                }
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                }
+            }
+
+            if !lost {
+                if grabbed {
+                    match dev.ungrab() {
+                        Ok(()) => println!("Input: Released touch device"),
+                        Err(e) => eprintln!("Input: Could not release touch device: {}", e),
+                    }
+                }
+                return;
+            }
+
+            // Device error or disconnect
+            eprintln!("Input: Touch device lost");
+            available.store(false, Ordering::Relaxed);
+            sleep_while(running, Duration::from_secs(1));
+        }
+    })
+}
+
+// Polls a sysfs GPIO line and queues a press on each inactive -> active edge,
+// until the returned worker is dropped
+pub(crate) fn spawn_gpio_thread(gpio: u32, active_low: bool, events: Arc<Mutex<Vec<bool>>>) -> Worker {
+    Worker::spawn(move |running| {
+        let value_path = format!("/sys/class/gpio/gpio{}/value", gpio);
+        if fs::metadata(&value_path).is_err() {
+            if let Err(e) = fs::write("/sys/class/gpio/export", gpio.to_string()) {
+                eprintln!("Input: Could not export GPIO {}: {}", gpio, e);
+            }
+            // Give udev a moment to set up the new attributes
+            thread::sleep(Duration::from_millis(100));
+            if let Err(e) = fs::write(format!("/sys/class/gpio/gpio{}/direction", gpio), "in") {
+                eprintln!("Input: Could not set GPIO {} as input: {}", gpio, e);
+            }
+        }
+
+        println!("Input: Watching doorbell on GPIO {}", gpio);
+        let mut was_pressed = false;
+        while running.load(Ordering::Relaxed) {
+            match fs::read_to_string(&value_path) {
+                Ok(value) => {
+                    let pressed = (value.trim() == "1") != active_low;
+                    if pressed && !was_pressed {
+                        if let Ok(mut q) = events.lock() {
+                            q.push(true);
+                        }
+                    }
+                    was_pressed = pressed;
+                    thread::sleep(Duration::from_millis(GPIO_POLL_MS));
+                }
+                Err(e) => {
+                    eprintln!("Input: Could not read GPIO {}: {}", gpio, e);
+                    sleep_while(running, Duration::from_secs(5));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_swipes_long_presses_and_taps() {
+        let long_press = Some(Duration::from_millis(800));
+        let start = Some(Point::new(400, 400));
+        let quick = Duration::from_millis(100);
+        let held = Duration::from_secs(1);
+
+        assert_eq!(classify(start, Some(Point::new(410, 250)), quick, long_press), Gesture::SwipeUp);
+        // Slow swipes are still swipes
+        assert_eq!(classify(start, Some(Point::new(410, 250)), held, long_press), Gesture::SwipeUp);
+        assert_eq!(classify(start, Some(Point::new(380, 490)), quick, long_press), Gesture::SwipeDown);
+        // Mostly sideways, or too short, is not
+        assert_eq!(classify(start, Some(Point::new(600, 300)), quick, long_press), Gesture::Tap);
+        assert_eq!(classify(start, Some(Point::new(400, 470)), held, long_press), Gesture::LongPress);
+        assert_eq!(classify(None, None, held, long_press), Gesture::LongPress);
+        assert_eq!(classify(None, None, held, None), Gesture::Tap);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::overlay::RenderContext;
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle, RoundedRectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use std::time::{Duration, Instant};
+
+const KEY_WIDTH: u32 = 90;
+const KEY_HEIGHT: u32 = 56;
+const KEY_GAP: u32 = 8;
+const MAX_DIGITS: usize = 3;
+const PANEL_BACKGROUND: Rgb888 = Rgb888::new(16, 16, 16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Digit(u8),
+    Cancel,
+    Confirm,
+}
+
+// Rows of the keypad, top to bottom
+const KEYS: [[Key; 3]; 4] = [
+    [Key::Digit(1), Key::Digit(2), Key::Digit(3)],
+    [Key::Digit(4), Key::Digit(5), Key::Digit(6)],
+    [Key::Digit(7), Key::Digit(8), Key::Digit(9)],
+    [Key::Cancel, Key::Digit(0), Key::Confirm],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeypadOutcome {
+    // Still open; redraw if `changed`
+    Open { changed: bool },
+    Closed,
+    // Zero-based camera index
+    Select(usize),
+}
+
+// Number pad for jumping straight to a camera by its 1-based position. An
+// out-of-range number clears the entry and shows `invalid` in its place.
+pub(crate) struct Keypad {
+    panel: Rectangle,
+    display: Rectangle,
+    keys: Vec<(Rectangle, Key)>,
+    timeout: Duration,
+    entry: String,
+    feedback: Option<String>,
+    last_input: Instant,
+}
+
+impl Keypad {
+    // Centred in the safe rect, with a display line above the keys
+    pub fn new(ctx: &RenderContext, font: &MonoFont, timeout: Duration) -> Self {
+        let safe = ctx.safe_rect();
+        let line = font.character_size.height + 2 * KEY_GAP;
+        let width = 3 * KEY_WIDTH + 4 * KEY_GAP;
+        let height = line + 4 * KEY_HEIGHT + 5 * KEY_GAP;
+        let top_left = safe.center() - Point::new(width as i32 / 2, height as i32 / 2);
+        let panel = Rectangle::new(top_left, Size::new(width, height));
+        let display = Rectangle::new(top_left + Point::new(KEY_GAP as i32, KEY_GAP as i32), Size::new(width - 2 * KEY_GAP, line - KEY_GAP));
+
+        let mut keys = Vec::new();
+        for (row, row_keys) in KEYS.iter().enumerate() {
+            for (col, &key) in row_keys.iter().enumerate() {
+                let x = top_left.x + (KEY_GAP + col as u32 * (KEY_WIDTH + KEY_GAP)) as i32;
+                let y = top_left.y + (line + KEY_GAP + row as u32 * (KEY_HEIGHT + KEY_GAP)) as i32;
+                keys.push((Rectangle::new(Point::new(x, y), Size::new(KEY_WIDTH, KEY_HEIGHT)), key));
+            }
+        }
+        Self { panel, display, keys, timeout, entry: String::new(), feedback: None, last_input: Instant::now() }
+    }
+
+    // Fresh entry when the keypad comes up
+    pub fn reset(&mut self, now: Instant) {
+        self.entry.clear();
+        self.feedback = None;
+        self.last_input = now;
+    }
+
+    // No key for the timeout
+    pub fn expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_input) >= self.timeout
+    }
+
+    // Handles a tap at `point`; taps beside the keys are ignored
+    pub fn tap(&mut self, point: Point, cameras: usize, invalid: &str, now: Instant) -> KeypadOutcome {
+        let Some(&(_, key)) = self.keys.iter().find(|(rect, _)| rect.contains(point)) else {
+            return KeypadOutcome::Open { changed: false };
+        };
+        self.last_input = now;
+        match key {
+            Key::Cancel => return KeypadOutcome::Closed,
+            Key::Digit(digit) => {
+                self.feedback = None;
+                if self.entry.len() < MAX_DIGITS {
+                    self.entry.push(char::from(b'0' + digit));
+                }
+            }
+            Key::Confirm => match self.entry.parse::<usize>() {
+                Ok(number) if (1..=cameras).contains(&number) => return KeypadOutcome::Select(number - 1),
+                // Nothing entered yet: nothing to complain about
+                Err(_) => {}
+                Ok(_) => {
+                    self.feedback = Some(invalid.replace("{number}", &self.entry));
+                    self.entry.clear();
+                }
+            },
+        }
+        KeypadOutcome::Open { changed: true }
+    }
+
+    pub fn draw<D>(&self, target: &mut D, fill: Rgb888, style: MonoTextStyle<'_, Rgb888>)
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        self.panel.into_styled(PrimitiveStyle::with_fill(PANEL_BACKGROUND)).draw(target).ok();
+        let centred = TextStyleBuilder::new().alignment(Alignment::Center).baseline(Baseline::Middle).build();
+
+        let shown = self.feedback.as_deref().unwrap_or(if self.entry.is_empty() { "#" } else { &self.entry });
+        Text::with_text_style(shown, self.display.center(), style, centred).draw(target).ok();
+
+        for (rect, key) in &self.keys {
+            RoundedRectangle::with_equal_corners(*rect, Size::new(8, 8))
+                .into_styled(PrimitiveStyle::with_fill(fill))
+                .draw(target)
+                .ok();
+            let label = match key {
+                Key::Digit(digit) => char::from(b'0' + digit).to_string(),
+                Key::Cancel => "X".to_string(),
+                Key::Confirm => "OK".to_string(),
+            };
+            Text::with_text_style(&label, rect.center(), style, centred).draw(target).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::config::{Insets, Theme};
+    use crate::framebuffer::FrameLayout;
+    use embedded_graphics::mono_font::ascii::FONT_10X20;
+    use gstreamer_video::VideoFormat;
+
+    fn key_center(keypad: &Keypad, key: Key) -> Point {
+        keypad.keys.iter().find(|(_, k)| *k == key).unwrap().0.center()
+    }
+
+    #[test]
+    fn selects_in_range_numbers_and_rejects_others() {
+        let theme = Theme::default();
+        let layout = FrameLayout { width: 800, height: 480, stride: 3200, format: VideoFormat::Bgra };
+        let ctx = RenderContext { layout, safe_area: Insets::default(), theme: &theme, clock: &SystemClock };
+        let mut keypad = Keypad::new(&ctx, &FONT_10X20, Duration::from_secs(10));
+        let now = Instant::now();
+        let press = |keypad: &mut Keypad, key| keypad.tap(key_center(keypad, key), 12, "No camera {number}", now);
+
+        // Confirming nothing keeps the keypad open
+        assert_eq!(press(&mut keypad, Key::Confirm), KeypadOutcome::Open { changed: true });
+        press(&mut keypad, Key::Digit(1));
+        press(&mut keypad, Key::Digit(4));
+        assert_eq!(press(&mut keypad, Key::Confirm), KeypadOutcome::Open { changed: true });
+        assert_eq!(keypad.feedback.as_deref(), Some("No camera 14"));
+        assert!(keypad.entry.is_empty());
+
+        press(&mut keypad, Key::Digit(1));
+        press(&mut keypad, Key::Digit(2));
+        assert_eq!(press(&mut keypad, Key::Confirm), KeypadOutcome::Select(11));
+        assert_eq!(press(&mut keypad, Key::Cancel), KeypadOutcome::Closed);
+        assert_eq!(keypad.tap(Point::new(1, 1), 12, "", now), KeypadOutcome::Open { changed: false });
+    }
+}
@@ -0,0 +1,906 @@
+mod actions;
+pub mod clock;
+pub mod config;
+pub mod error;
+pub mod events;
+pub mod framebuffer;
+mod freeze;
+mod health;
+mod idle;
+mod input;
+mod ptz;
+pub mod overlay;
+mod keypad;
+mod overview;
+pub mod pipeline;
+mod pool;
+mod rotation;
+mod snapshot;
+mod stats;
+mod worker;
+
+use embedded_graphics::{
+    mono_font::{iso_8859_1::FONT_10X20, MonoTextStyle},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::Rectangle,
+    text::Text,
+};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub use config::{CameraConfig, Codec, Config};
+pub use error::{Error, Result};
+use clock::{unix_seconds, ClockJump, JumpDetector, SystemClock};
+use config::{ButtonsConfig, DndDecode, Layer, ReconnectDisplay, SourceType, TouchMissing, TouchWhileConnecting};
+use events::{Event, EventBus, SwitchReason};
+use framebuffer::{set_blank, FrameLayout, Framebuffer, Unblanker};
+use health::{HealthEvent, HealthLog};
+use overlay::{RenderContext, PROGRESS_BAR_HEIGHT};
+use keypad::{Keypad, KeypadOutcome};
+use overview::Overview;
+use input::{spawn_gpio_thread, spawn_input_thread, touch_device, Gesture};
+use pipeline::{open_camera_sized, ConnectionState, PipelineShare, VideoPipeline};
+use pool::BufferPool;
+use rotation::{neighbour, DwellTimer};
+use snapshot::Burst;
+use freeze::{FreezeChange, FreezeDetector};
+use stats::{Bench, FrameGuard, Heartbeat, LatencyReport, Stats};
+
+// --- Constants ---
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const FRAME_POLL_MS: u64 = 10; // wait for a sample while active
+pub(crate) const FRAME_WIDTH: u32 = 800;
+pub(crate) const FRAME_HEIGHT: u32 = 480;
+// Panel configs are checked against before a display reports its real size
+pub(crate) const SCREEN: FrameLayout = FrameLayout { width: FRAME_WIDTH, height: FRAME_HEIGHT, stride: FRAME_WIDTH * 4, format: gstreamer_video::VideoFormat::Bgra };
+const SETUP_GRID_SPACING: u32 = 40;
+const DEFAULT_FRAMEBUFFER: &str = "/dev/fb0";
+const HELD_FRAME_BRIGHTNESS: u8 = 96; // previous camera's frame while the next one connects
+const SETUP_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+const ACTION_NOTICE: Duration = Duration::from_secs(3); // acknowledgement of an action button
+const OVERVIEW_REDRAW: Duration = Duration::from_secs(1); // keeps the "seen ... ago" ages current
+
+// --- Main Loop ---
+/// Runs the viewer until `running` is cleared or `max_runtime_hours` is reached.
+///
+/// Installs no signal handlers; the binary clears `running` on Ctrl-C and
+/// SIGTERM. Every thread it starts (touch input, doorbell GPIO, the event
+/// socket listener) is stopped and joined before it returns, and the event
+/// socket is removed, so it can be called again, e.g. with a reloaded config,
+/// and take the same devices and socket path. Pipelines still tearing down in
+/// the background, and short-lived action or snapshot threads, are left to
+/// finish on their own; they hold nothing the next run needs.
+///
+/// With several `displays`, each gets its own render loop thread, and when one
+/// stops (or fails) `running` is cleared so the others stop too.
+pub fn run(config: &Config, running: &AtomicBool) -> Result<()> {
+    gstreamer::init()?;
+    if config.force_software_decode {
+        eprintln!("Decoder: WARNING: software decoding forced for all cameras, expect much higher CPU use");
+    }
+    // Settles decoder fallbacks up front, before for_display copies the config
+    for codec in [Codec::H264, Codec::H265] {
+        if config.cameras.iter().any(|c| c.codec == codec && c.pipeline.is_none() && c.source_type != SourceType::Image) {
+            println!("Decoder: {:?} streams use {}", codec, config.select_decoder(codec));
+        }
+    }
+
+    if config.displays.is_empty() {
+        return run_display(config, DEFAULT_FRAMEBUFFER, true, None, &PipelineShare::default(), running);
+    }
+
+    let displays = config.displays.iter().enumerate()
+        .map(|(i, display)| Ok((display, config.for_display(display, i == 0)?)))
+        .collect::<Result<Vec<_>>>()?;
+    // A camera on several displays is decoded once for all of them
+    let mut seen = HashSet::new();
+    let shared = displays.iter()
+        .flat_map(|(_, display_config)| display_config.cameras.iter().map(|c| c.name.clone()))
+        .filter(|name| !seen.insert(name.clone()))
+        .collect();
+    let share = PipelineShare::new(shared);
+    thread::scope(|scope| {
+        let handles: Vec<_> = displays.iter().map(|(display, display_config)| {
+            let share = &share;
+            scope.spawn(move || {
+                println!("Display: Starting {}", display.framebuffer);
+                let result = run_display(display_config, &display.framebuffer, display.touch, display.input.as_deref(), share, running);
+                if let Err(e) = &result {
+                    eprintln!("Display: {} stopped: {}", display.framebuffer, e);
+                }
+                running.store(false, Ordering::Relaxed);
+                result
+            })
+        }).collect();
+        // Every thread is joined before the first error is returned
+        let results: Vec<_> = handles.into_iter()
+            .map(|h| h.join().unwrap_or_else(|_| Err(Error::Config("Display thread panicked".to_string()))))
+            .collect();
+        results.into_iter().collect::<Result<Vec<_>>>()
+    })?;
+    Ok(())
+}
+
+/// Shows setup instructions while `config` has no cameras, on every display.
+///
+/// `reload` is called every few seconds; the first config it returns with
+/// cameras is handed back so the caller can start `run` with it. Returns
+/// `None` if `running` is cleared first. `config_path` is shown as where to
+/// put the config, next to the device's IP addresses.
+pub fn wait_for_cameras(
+    config: &Config,
+    config_path: &str,
+    running: &AtomicBool,
+    mut reload: impl FnMut() -> Option<Config>,
+) -> Result<Option<Config>> {
+    let paths: Vec<&str> = match config.displays.is_empty() {
+        true => vec![DEFAULT_FRAMEBUFFER],
+        false => config.displays.iter().map(|d| d.framebuffer.as_str()).collect(),
+    };
+    let mut fbs = paths.into_iter().map(Framebuffer::new).collect::<Result<Vec<_>>>()?;
+    let style = MonoTextStyle::new(&FONT_10X20, config.theme.text.0);
+    let logo = config.logo.as_ref().and_then(overlay::Logo::load);
+    println!("Setup: No cameras configured, waiting for {}", config_path);
+
+    let mut shown = None;
+    while running.load(Ordering::Relaxed) {
+        // Redrawn when the text changes, e.g. once DHCP hands out an address
+        let text = idle::setup_text(&config.messages.no_cameras, config_path);
+        if shown.as_ref() != Some(&text) {
+            for fb in &mut fbs {
+                let ctx = RenderContext { layout: fb.layout(), safe_area: config.safe_area, theme: &config.theme, clock: &SystemClock };
+                overlay::draw_background(fb, &ctx);
+                overlay::draw_paragraphs(fb, &ctx, &text, style);
+                if let Some(logo) = &logo {
+                    logo.draw(fb, &ctx);
+                }
+            }
+            shown = Some(text);
+        }
+
+        let deadline = Instant::now() + SETUP_RELOAD_INTERVAL;
+        while running.load(Ordering::Relaxed) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(FRAME_POLL_MS));
+        }
+        if let Some(new_config) = reload().filter(|c| !c.cameras.is_empty()) {
+            println!("Setup: Found {} camera(s), starting", new_config.cameras.len());
+            return Ok(Some(new_config));
+        }
+    }
+    Ok(None)
+}
+
+fn run_display(config: &Config, fb_path: &str, touch: bool, input: Option<&str>, share: &PipelineShare, running: &AtomicBool) -> Result<()> {
+    let cameras = &config.cameras;
+    if cameras.is_empty() { return Err(Error::Config("No cameras defined".to_string())); }
+
+    let mut fb = match Framebuffer::new(fb_path) {
+        Ok(fb) => fb,
+        Err(e) => {
+            eprintln!("Error opening FB: {}", e);
+            return Err(e);
+        }
+    };
+
+    let events = EventBus::default();
+    if let Some(path) = &config.event_socket {
+        events.listen(path)?;
+    }
+
+    let input = input.map(PathBuf::from);
+    if touch && config.touch_missing == TouchMissing::Fail && touch_device(input.as_deref()).is_none() {
+        return Err(Error::Input("No touch device found".to_string()));
+    }
+    let touch_queue = Arc::new(Mutex::new(Vec::new()));
+    let touch_available = Arc::new(AtomicBool::new(false));
+    // Stops the input thread, and releases a grab, when this display stops
+    let _input = touch.then(|| {
+        let long_press = config.overview.as_ref().map(|o| Duration::from_millis(o.long_press_ms));
+        spawn_input_thread(input, touch_queue.clone(), touch_available.clone(), config.debug_input, long_press, config.grab_input)
+    });
+
+    let doorbell_queue = Arc::new(Mutex::new(Vec::new()));
+    let _doorbell = config.doorbell.as_ref().map(|doorbell| spawn_gpio_thread(doorbell.gpio, doorbell.active_low, doorbell_queue.clone()));
+    let doorbell_idx = config.doorbell.as_ref().and_then(|d| d.camera.resolve(cameras));
+    let home_idx = config.home_camera.as_ref().and_then(|c| c.resolve(cameras));
+    let home_after = Duration::from_secs(config.home_after_seconds);
+    // Last touch navigation, until the display returns home
+    let mut last_touch: Option<Instant> = None;
+    // Camera to return to, and when, while a doorbell press holds the display
+    let mut doorbell_hold: Option<(usize, Instant)> = None;
+    let mut burst: Option<Burst> = None;
+    let buffer_pool = BufferPool::new(config.buffer_pool_size);
+
+    let started = Instant::now();
+    let max_runtime = config.max_runtime_hours.map(|h| Duration::from_secs(h * 3600));
+    if let Some(hours) = config.max_runtime_hours {
+        println!("Restart: Scheduled clean exit after {}h", hours);
+    }
+
+    let mut current_idx = config.startup_index();
+    // Custom pipelines that delivered the wrong format or size, converted from then on
+    let mut convert = vec![false; cameras.len()];
+    let mut pipeline_wrapper = share.open(config, &cameras[current_idx], convert[current_idx])?;
+    let mut dwell_timer = DwellTimer::new(Instant::now());
+    let mut pending_tap = None;
+    // Current camera was picked by touch, so it stays for the manual dwell
+    let mut manual_view = false;
+    let mut reconnect_at: Option<Instant> = None;
+    let mut state = ConnectionState::Connecting;
+    let mut shown_state = None;
+    // Where the status screen's spinner is, since when, and the step on screen
+    let mut spinner: Option<(Rectangle, Instant, Option<u32>)> = None;
+    // Display off on the dnd schedule, until a tap wakes it until the given time
+    let mut dnd = false;
+    let mut dnd_wake_until: Option<Instant> = None;
+    // Pipeline left paused by dnd decode = pause, to resume instead of reopen
+    let mut dnd_paused = false;
+    let health = config.health_log.as_ref().map(HealthLog::new);
+    let log_health = |camera: &str, event| {
+        if let Some(health) = &health {
+            health.record(camera, event);
+        }
+    };
+    log_health(&cameras[current_idx].name, HealthEvent::Connecting);
+
+    let idle_wait_ms = config.idle_sleep_ms.max(FRAME_POLL_MS);
+    let mut idle = false;
+
+    let mut stats = Stats::default();
+    let mut heartbeat = config.heartbeat_seconds.map(|s| Heartbeat::new(Duration::from_secs(s)));
+    let mut clock_jumps = JumpDetector::default();
+    let mut unblanker = config.unblank.as_ref().map(|u| Unblanker::new(&u.path, Duration::from_secs(u.interval_seconds)));
+    let mut latency_report = config.latency_report_seconds.map(|s| LatencyReport::new(Duration::from_secs(s)));
+    let measure_latency = latency_report.is_some() || config.latency_overlay;
+    let mut frame_guard = config.slow_frame_ms.map(|ms| FrameGuard::new(Duration::from_millis(ms)));
+    let mut freeze = config.freeze_detect.as_ref().map(FreezeDetector::new);
+    // Only set with --bench; otherwise no extra timestamps are taken
+    let mut bench = config.bench.as_ref().map(|b| Bench::new(b.frames));
+
+    let name_style = MonoTextStyle::new(&FONT_10X20, config.theme.text.0);
+    // The status bar strip is kept out of the video blit and the floating overlays
+    let video_area = config.video_insets();
+    let screen = fb.layout();
+    // Frames come unpadded; the blit spreads them over the device's own line length
+    let video_layout = FrameLayout { height: screen.height - video_area.top - video_area.bottom, stride: screen.width * 4, ..screen };
+    let mut shown_bar = None;
+    // Last video frame without overlays and the camera it came from, kept only
+    // with hold_last_frame or for a camera that holds it while reconnecting
+    let mut last_frame: Vec<u8> = Vec::new();
+    let mut last_frame_camera = current_idx;
+    let ctx = RenderContext { layout: screen, safe_area: config.safe_area.plus(video_area), theme: &config.theme, clock: &SystemClock };
+    let nav_buttons = overlay::NavButtons::new(config.buttons.as_ref(), &ctx);
+    let mut camera_buttons = overlay::CameraButtons::new(&cameras[current_idx].button_labels(), &ctx, name_style.font);
+    let camera_button_fill = config.buttons.as_ref().map_or(ButtonsConfig::default().fill, |b| b.fill).0;
+    let action_results: Arc<Mutex<Vec<actions::ActionResult>>> = Arc::new(Mutex::new(Vec::new()));
+    // Acknowledgement text and until when it shows
+    let mut action_notice: Option<(String, Instant)> = None;
+    let logo = config.logo.as_ref().and_then(overlay::Logo::load);
+    // Tile dashboard in place of the video, toggled by a long press
+    let video_rect = Rectangle::new(Point::new(0, video_area.top as i32), Size::new(screen.width, video_layout.height));
+    let mut overview = config.overview.as_ref().map(|o| {
+        Overview::new(cameras.len(), &ctx, video_rect, name_style.font.character_size.height, Duration::from_secs(o.thumbnail_seconds))
+    });
+    let mut overview_open = false;
+    let mut overview_drawn: Option<Instant> = None;
+    // Camera number entry over the frozen video, opened by a swipe up
+    let mut keypad = config.keypad.as_ref().map(|k| Keypad::new(&ctx, name_style.font, Duration::from_secs(k.timeout_seconds)));
+    let mut keypad_open = false;
+    let mut keypad_drawn = false;
+    // Second camera in a corner, composited after the overlays; Config::check_layout
+    // made sure it misses them
+    let pip_area = overlay::pip_window(config, &ctx, name_style.font, touch).map_err(Error::Config)?.map(|rect| {
+        let layout = FrameLayout { width: rect.size.width, height: rect.size.height, stride: rect.size.width * 4, ..screen };
+        (layout, rect.top_left)
+    });
+    let mut pip_idx = config.pip.as_ref().and_then(|p| p.camera.resolve(cameras));
+    // Running PiP pipeline with its camera, and its latest frame for redrawing over each main frame
+    let mut pip: Option<(usize, VideoPipeline)> = None;
+    let mut pip_frame: Vec<u8> = Vec::new();
+    let mut pip_retry_at: Option<Instant> = None;
+    // Rotation and touch navigation stay within this group; swiping down picks the next one
+    let mut active_group = config.cycle_group.clone();
+    let group_names = config.group_names();
+
+    println!("Starting loop for camera: {}", cameras[current_idx].name);
+    if !config.auto_cycle {
+        println!("Auto-cycle disabled, switching on touch only");
+    }
+
+    while running.load(Ordering::Relaxed) {
+        // 1. Check Video
+        // Nothing changed last time around: block longer, a new frame still wakes us
+        let wait_ms = if idle { idle_wait_ms } else { FRAME_POLL_MS };
+        idle = true;
+
+        let mut failure = None;
+        let pull_start = bench.is_some().then(Instant::now);
+        if dnd || reconnect_at.is_some() {
+            // Stopped pipeline returns immediately; avoid spinning while waiting
+            thread::sleep(Duration::from_millis(wait_ms));
+        } else if let Some(sample) = pipeline_wrapper.sink.try_pull_sample(gstreamer::ClockTime::from_mseconds(wait_ms)) {
+            let pulled_at = Instant::now();
+            idle = false;
+            stats.frames += 1;
+            let buffer = sample.buffer().ok_or_else(|| Error::pipeline(&cameras[current_idx].name, "No buffer"))?;
+            let expected = video_layout;
+            let mismatch = match sample.caps().and_then(FrameLayout::from_caps) {
+                Some(layout) => layout.mismatch(&expected, buffer.size()),
+                None => Some("sample has no video caps".to_string()),
+            };
+
+            if let Some(reason) = mismatch {
+                // Wrong or changed caps: restart rather than blit a skewed or partial frame. A
+                // custom pipeline gets a conversion added, once; after that it's a real error.
+                let camera = &cameras[current_idx];
+                if camera.pipeline.is_some() && !convert[current_idx] {
+                    eprintln!("Pipeline: {} delivers unusable frames ({}), restarting with a conversion to BGRA", camera.name, reason);
+                    convert[current_idx] = true;
+                }
+                failure = Some(format!("Unexpected frame layout: {}", reason));
+            } else {
+                if state != ConnectionState::Live {
+                    log_health(&cameras[current_idx].name, HealthEvent::Connected);
+                    // A new pipeline starts with a clean slate
+                    if let Some(detector) = &mut freeze {
+                        detector.reset();
+                    }
+                }
+                state = ConnectionState::Live;
+                let map = buffer.map_readable()?;
+                if let Some(overview) = &mut overview {
+                    overview.frame(current_idx, expected, &map, pulled_at);
+                }
+
+                // Blit to FB, unless the overview or keypad covers the video
+                let copy_start = Instant::now();
+                if !overview_open && !keypad_open {
+                    fb.blit(&map, expected, video_area.top);
+                }
+                if config.hold_last_frame || config.reconnect_display(&cameras[current_idx]) == ReconnectDisplay::LastFrame {
+                    last_frame.clear();
+                    last_frame.extend_from_slice(&map[..expected.stride as usize * expected.height as usize]);
+                    last_frame_camera = current_idx;
+                }
+                let copy_time = copy_start.elapsed();
+                // Stills repeat the same picture by design
+                let detector = freeze.as_mut().filter(|_| cameras[current_idx].source_type != SourceType::Image);
+                match detector.and_then(|d| d.check(expected, &map, pulled_at)) {
+                    Some(FreezeChange::Frozen(unchanged)) => {
+                        let name = &cameras[current_idx].name;
+                        eprintln!("Freeze: {} picture unchanged for {}s", name, unchanged.as_secs());
+                        log_health(name, HealthEvent::Frozen { seconds: unchanged.as_secs() });
+                        if config.freeze_detect.as_ref().is_some_and(|f| f.restart) {
+                            failure = Some(format!("Picture frozen for {}s", unchanged.as_secs()));
+                        }
+                    }
+                    Some(FreezeChange::Thawed) => println!("Freeze: {} picture moving again", cameras[current_idx].name),
+                    None => {}
+                }
+                stats.frozen = freeze.as_ref().is_some_and(|d| d.frozen());
+                if let (Some(b), Some(settings)) = (&mut burst, config.doorbell.as_ref().and_then(|d| d.burst.as_ref())) {
+                    if b.due(pulled_at) && b.capture(settings, expected, &map, &buffer_pool, pulled_at) {
+                        burst = None;
+                    }
+                }
+                let frame_delay = if measure_latency { pipeline_wrapper.frame_delay(&sample) } else { None };
+                if let (Some(report), Some(delay)) = (&mut latency_report, frame_delay) {
+                    report.record(delay);
+                }
+
+                // Release the sample right away so the decoder gets its buffer back
+                drop(map);
+                drop(sample);
+                let hold_time = pulled_at.elapsed();
+                stats.record_timing(copy_time, hold_time);
+                if let Some(guard) = &mut frame_guard {
+                    guard.check(&mut stats, &cameras[current_idx].name, copy_time, hold_time);
+                }
+
+                // 2. Draw Overlay, bottom layer first; the overview and keypad have none
+                let overlay_start = bench.is_some().then(Instant::now);
+                let visible = config.layers.iter().filter(|&&layer| config.shows_layer(&cameras[current_idx], layer));
+                for layer in visible.filter(|_| !overview_open && !keypad_open) {
+                    match layer {
+                        Layer::Name => {
+                            let name = &cameras[current_idx].name;
+                            let anchor = config.name_anchor(&cameras[current_idx]);
+                            let style = MonoTextStyle::new(name_style.font, config.name_color(&cameras[current_idx]).0);
+                            overlay::draw_anchored_text(&mut fb.blended(config.overlay_alpha), &ctx, name, style, anchor);
+                        }
+                        Layer::Buttons => nav_buttons.draw(&mut fb.blended(config.overlay_alpha), config.theme.arrows.0),
+                        Layer::CameraButtons => {
+                            camera_buttons.draw(&mut fb.blended(config.overlay_alpha), camera_button_fill, name_style);
+                            if let Some((text, _)) = action_notice.as_ref().filter(|(_, until)| pulled_at < *until) {
+                                camera_buttons.draw_notice(&mut fb.blended(config.overlay_alpha), &ctx, text, name_style);
+                            }
+                        }
+                        Layer::ProgressBar if config.progress_bar && config.auto_cycle => {
+                            let dwell = config.view_dwell(&cameras[current_idx], manual_view);
+                            let fraction = dwell_timer.progress(Instant::now(), dwell);
+                            overlay::draw_progress_bar(&mut fb.blended(config.overlay_alpha), &ctx, fraction, PROGRESS_BAR_HEIGHT);
+                        }
+                        Layer::TouchNotice if touch && config.touch_missing == TouchMissing::Notice && !touch_available.load(Ordering::Relaxed) => {
+                            let notice = &config.messages.touch_unavailable;
+                            overlay::draw_wrapped_text(&mut fb.blended(config.overlay_alpha), notice, name_style, overlay::touch_notice_top(&ctx), ctx.text_width());
+                        }
+                        Layer::Latency => {
+                            if let Some(delay) = frame_delay.filter(|_| config.latency_overlay) {
+                                let label = format!("{} ms", delay.mseconds());
+                                Text::new(&label, overlay::latency_origin(&ctx), name_style).draw(&mut fb.blended(config.overlay_alpha)).ok();
+                            }
+                        }
+                        Layer::Logo => {
+                            if let Some(logo) = &logo {
+                                logo.draw(&mut fb, &ctx);
+                            }
+                        }
+                        Layer::SetupGuides if config.setup_mode => overlay::draw_setup_guides(&mut fb, &ctx, SETUP_GRID_SPACING),
+                        _ => {}
+                    }
+                }
+                if let Some((layout, origin)) = pip_area.filter(|_| !pip_frame.is_empty() && !overview_open && !keypad_open) {
+                    fb.blit_at(&pip_frame, layout, origin);
+                }
+
+                if let (Some(b), Some(pull_start), Some(overlay_start)) = (&mut bench, pull_start, overlay_start) {
+                    b.record(pulled_at - pull_start, copy_time, overlay_start.elapsed());
+                    if b.done() {
+                        b.report();
+                        break;
+                    }
+                }
+            }
+        }
+
+        // A pipeline stopped for DND has nothing to reconnect
+        let failure = if dnd { None } else { failure.or_else(|| pipeline_wrapper.poll_error()) };
+        if let Some(message) = failure {
+            eprintln!("Pipeline: {} error: {}", cameras[current_idx].name, message);
+            log_health(&cameras[current_idx].name, HealthEvent::Error { message: message.clone() });
+            pipeline_wrapper.fail(&message);
+            events.emit(Event::PipelineError { camera: &cameras[current_idx].name, message });
+            idle = false;
+            pipeline_wrapper.stop()?;
+            reconnect_at = Some(Instant::now() + RECONNECT_DELAY);
+            state = ConnectionState::Reconnecting;
+        }
+
+        if reconnect_at.is_some_and(|t| Instant::now() >= t) {
+            reconnect_at = None;
+            idle = false;
+            stats.reconnects += 1;
+            println!("Pipeline: Reconnecting to {}", cameras[current_idx].name);
+            log_health(&cameras[current_idx].name, HealthEvent::Reconnecting);
+            pipeline_wrapper = share.open(config, &cameras[current_idx], convert[current_idx])?;
+        }
+
+        // The DND schedule is re-read from the wall clock every iteration, so it
+        // follows a step right away. Touch wakes and timers run on Instant and
+        // keep their deadlines.
+        let jump = clock_jumps.check(&SystemClock);
+        match jump {
+            Some(ClockJump::Forward(d)) => println!("Clock: Wall clock stepped forward by {}s", d.as_secs()),
+            Some(ClockJump::Backward(d)) => println!("Clock: Wall clock stepped back by {}s", d.as_secs()),
+            None => {}
+        }
+        if let Some(window) = config.dnd.as_ref().filter(|_| jump.is_some()) {
+            println!("DND: Schedule is {} at the new time", if window.scheduled(&SystemClock) { "on" } else { "off" });
+        }
+
+        if let Some(report) = latency_report.as_mut().filter(|r| r.due()) {
+            report.log(&cameras[current_idx].name, pipeline_wrapper.query_latency());
+        }
+
+        if let Some(unblanker) = unblanker.as_mut().filter(|_| !dnd) {
+            unblanker.tick();
+        }
+
+        if let Some(heartbeat) = &mut heartbeat {
+            (stats.buffer_allocations, stats.buffer_reuses) = buffer_pool.counts();
+            heartbeat.tick(&mut stats, &cameras[current_idx].name);
+        }
+
+        // 3. Logic: Check for Click, Timeout or Doorbell
+        let now = Instant::now();
+        let next_idx = neighbour(cameras.len(), current_idx, true, |i| config.in_group(&cameras[i], active_group.as_deref()));
+        let mut switch_to = None;
+        {
+            // Oldest first, one per iteration, so none is lost; while a tap waits
+            // for a connecting camera the later ones stay queued behind it
+            let mut q = touch_queue.lock().unwrap();
+            let tap = (pending_tap.is_none() && !q.is_empty()).then(|| q.remove(0));
+            drop(q);
+            if let Some(tap) = tap {
+                println!("Touch detected!");
+                if tap.gesture == Gesture::SwipeUp && !dnd {
+                    if let Some(keypad) = &mut keypad {
+                        keypad.reset(now);
+                        keypad_open = true;
+                        keypad_drawn = false;
+                        overview_open = false;
+                        println!("Keypad: Opened");
+                    } else {
+                        pending_tap = Some(tap.point);
+                    }
+                } else if tap.gesture == Gesture::SwipeDown && !group_names.is_empty() && !dnd {
+                    // All cameras, then each group in turn
+                    let position = active_group.as_deref().and_then(|g| group_names.iter().position(|name| *name == g));
+                    let next_group = match position {
+                        None => group_names.first(),
+                        Some(i) => group_names.get(i + 1),
+                    };
+                    active_group = next_group.map(|name| name.to_string());
+                    println!("Groups: Rotating {}", active_group.as_deref().unwrap_or("all cameras"));
+                    let group = active_group.as_deref();
+                    if !config.in_group(&cameras[current_idx], group) {
+                        let index = neighbour(cameras.len(), current_idx, true, |i| config.in_group(&cameras[i], group));
+                        switch_to = Some((index, SwitchReason::Touch));
+                    }
+                } else if tap.gesture == Gesture::LongPress && overview.is_some() && !dnd {
+                    overview_open = !overview_open;
+                    overview_drawn = None;
+                    keypad_open = false;
+                    // Closing: the next frame or the status screen repaints the video area
+                    shown_state = None;
+                    println!("Overview: {}", if overview_open { "Opened" } else { "Closed" });
+                } else {
+                    pending_tap = Some(tap.point);
+                }
+            }
+        }
+        if let (true, Some(window), Some(_)) = (dnd, &config.dnd, pending_tap) {
+            // A tap on the dark screen only wakes it
+            pending_tap = None;
+            dnd_wake_until = Some(now + Duration::from_secs(window.wake_seconds));
+            println!("DND: Woken by touch for {}s", window.wake_seconds);
+        }
+        if let Some(result) = action_results.lock().unwrap().pop() {
+            let template = if result.ok { &config.messages.action_done } else { &config.messages.action_failed };
+            action_notice = Some((template.replace("{action}", &result.name), now + ACTION_NOTICE));
+        }
+        // The keypad takes every tap while open, and closes itself when left alone
+        if let (true, Some(pad)) = (keypad_open, &mut keypad) {
+            let outcome = match pending_tap.take() {
+                Some(Some(point)) => pad.tap(point, cameras.len(), &config.messages.keypad_invalid, now),
+                None if pad.expired(now) => KeypadOutcome::Closed,
+                // Without a position there is no key to press
+                _ => KeypadOutcome::Open { changed: false },
+            };
+            match outcome {
+                KeypadOutcome::Open { changed } => keypad_drawn &= !changed,
+                KeypadOutcome::Closed | KeypadOutcome::Select(_) => {
+                    keypad_open = false;
+                    shown_state = None;
+                    println!("Keypad: Closed");
+                }
+            }
+            if let KeypadOutcome::Select(index) = outcome {
+                switch_to = (index != current_idx).then_some((index, SwitchReason::Touch));
+                doorbell_hold = None;
+                last_touch = Some(now);
+            }
+        }
+        // In the overview a tap opens that tile's camera; anywhere else just closes it
+        if let (true, Some(tap), Some(tiles)) = (overview_open, pending_tap, &overview) {
+            pending_tap = None;
+            overview_open = false;
+            shown_state = None;
+            if let Some(index) = tap.and_then(|point| tiles.hit(point)) {
+                switch_to = (index != current_idx).then_some((index, SwitchReason::Touch));
+                doorbell_hold = None;
+                last_touch = Some(now);
+            }
+        }
+        // A tap on the PiP swaps it with the main view
+        if let (Some((index, _)), Some((layout, origin))) = (&pip, pip_area) {
+            let window = Rectangle::new(origin, Size::new(layout.width, layout.height));
+            if pending_tap.flatten().is_some_and(|point| window.contains(point)) {
+                pending_tap = None;
+                switch_to = Some((*index, SwitchReason::Touch));
+                pip_idx = Some(current_idx);
+                doorbell_hold = None;
+                last_touch = Some(now);
+            }
+        }
+        // In queue mode a tap waits for the current camera to connect
+        let hold_taps = config.touch_while_connecting == TouchWhileConnecting::Queue && state != ConnectionState::Live;
+        if !hold_taps {
+            if let Some(button) = pending_tap.flatten().and_then(|point| camera_buttons.hit(point)) {
+                // Presets move the camera and actions run in the background; both stay on it
+                pending_tap = None;
+                last_touch = Some(now);
+                let camera = &cameras[current_idx];
+                let presets = camera.ptz_presets().len();
+                if let (Some(ptz), true) = (&camera.ptz, button < presets) {
+                    ptz::goto_preset(camera, ptz, button);
+                } else if let Some(action) = camera.actions.get(button - presets) {
+                    actions::trigger(&camera.name, action, action_results.clone());
+                }
+            }
+            if let Some(tap) = pending_tap.take() {
+                // The previous button goes back; anywhere else advances
+                let index = match tap.and_then(|point| nav_buttons.hit(point)) {
+                    Some(overlay::NavButton::Previous) => {
+                        neighbour(cameras.len(), current_idx, false, |i| config.in_group(&cameras[i], active_group.as_deref()))
+                    }
+                    _ => next_idx,
+                };
+                switch_to = Some((index, SwitchReason::Touch));
+                // Navigating by hand ends a doorbell hold
+                doorbell_hold = None;
+                last_touch = Some(now);
+            }
+        }
+
+        // Back home once nobody has touched the screen for a while; takes priority over auto-cycle
+        if let Some(home) = home_idx.filter(|_| !dnd && switch_to.is_none() && doorbell_hold.is_none()) {
+            if last_touch.is_some_and(|t| now.saturating_duration_since(t) >= home_after) {
+                last_touch = None;
+                if home != current_idx {
+                    println!("Home: No touch for {}s, returning to {}", home_after.as_secs(), cameras[home].name);
+                    switch_to = Some((home, SwitchReason::Home));
+                }
+            }
+        }
+
+        let dwell = config.view_dwell(&cameras[current_idx], manual_view);
+        if config.auto_cycle && !dnd && switch_to.is_none() && doorbell_hold.is_none() && dwell_timer.expired(now, dwell) {
+            let index = neighbour(cameras.len(), current_idx, true, |i| config.cycles_to(&cameras[i], active_group.as_deref()));
+            if index != current_idx {
+                switch_to = Some((index, SwitchReason::AutoCycle));
+                println!("Auto-cycling...");
+            } else {
+                // Nowhere else to go: hold rather than reconnect to the same camera
+                dwell_timer.restart(now);
+            }
+        }
+
+        if let Some((previous, _)) = doorbell_hold.filter(|&(_, until)| now >= until) {
+            doorbell_hold = None;
+            println!("Doorbell: Hold over, resuming rotation");
+            if previous != current_idx {
+                switch_to = Some((previous, SwitchReason::Resume));
+            } else {
+                // Still on the doorbell camera: rotation starts over from here
+                dwell_timer.restart(now);
+            }
+        }
+
+        // Highest priority: overrides touch and auto-cycle from this iteration
+        if let (Some(target), Some(doorbell)) = (doorbell_idx, &config.doorbell) {
+            let mut q = doorbell_queue.lock().unwrap();
+            if !q.is_empty() {
+                q.clear();
+                // Repeated presses extend the hold but keep the original camera to return to
+                let previous = doorbell_hold.map_or(current_idx, |(previous, _)| previous);
+                doorbell_hold = Some((previous, now + Duration::from_secs(doorbell.hold_seconds)));
+                println!("Doorbell: Pressed, showing {}", cameras[target].name);
+                overview_open = false;
+                keypad_open = false;
+                shown_state = None;
+                // A press during a burst doesn't start another one
+                if doorbell.burst.is_some() && burst.is_none() {
+                    burst = Some(Burst::new(format!("doorbell-{}", unix_seconds(&SystemClock)), target, now));
+                }
+                switch_to = (target != current_idx).then_some((target, SwitchReason::Doorbell));
+            }
+        }
+
+        if let Some((index, reason)) = switch_to {
+            idle = false;
+            if state == ConnectionState::Live {
+                pipeline_wrapper.stop()?;
+            } else {
+                // A pipeline still connecting can block in teardown; don't wait for it
+                pipeline_wrapper.abandon();
+            }
+            current_idx = index;
+            overview_drawn = None;
+            camera_buttons = overlay::CameraButtons::new(&cameras[current_idx].button_labels(), &ctx, name_style.font);
+            // Stills only come from the doorbell camera
+            if burst.as_ref().is_some_and(|b| b.camera() != current_idx) {
+                burst = None;
+            }
+            manual_view = matches!(reason, SwitchReason::Touch);
+            dwell_timer.restart(Instant::now());
+            println!("Switching to: {}", cameras[current_idx].name);
+            log_health(&cameras[current_idx].name, HealthEvent::Connecting);
+            pipeline_wrapper = share.open(config, &cameras[current_idx], convert[current_idx])?;
+            reconnect_at = None;
+            state = ConnectionState::Connecting;
+            events.emit(Event::Switched { camera: &cameras[current_idx].name, index: current_idx, reason });
+        }
+
+        // Do-not-disturb: dark on schedule, except during a doorbell hold or a touch wake
+        if let Some(window) = &config.dnd {
+            let scheduled = window.scheduled(&SystemClock);
+            if !scheduled {
+                dnd_wake_until = None;
+            }
+            let woken = doorbell_hold.is_some() || dnd_wake_until.is_some_and(|until| now < until);
+            let want_dnd = scheduled && !woken;
+            if want_dnd != dnd {
+                dnd = want_dnd;
+                idle = false;
+                if dnd {
+                    println!("DND: Entering do-not-disturb, display off");
+                    overview_open = false;
+                    keypad_open = false;
+                    // Only a live stream is worth keeping; one still connecting is reopened on wake
+                    dnd_paused = window.decode == DndDecode::Pause && state == ConnectionState::Live;
+                    if dnd_paused {
+                        pipeline_wrapper.pause()?;
+                    } else if state == ConnectionState::Live {
+                        pipeline_wrapper.stop()?;
+                    } else {
+                        pipeline_wrapper.abandon();
+                    }
+                    reconnect_at = None;
+                    // Hours later the held frame would only mislead
+                    last_frame.clear();
+                    fb.clear(Rgb888::BLACK).ok();
+                    if window.dpms {
+                        set_blank(&window.blank_path, true);
+                    }
+                } else {
+                    println!("DND: Leaving do-not-disturb");
+                    if window.dpms {
+                        set_blank(&window.blank_path, false);
+                    }
+                    // A doorbell switch this iteration already opened its camera
+                    if switch_to.is_none() {
+                        state = ConnectionState::Connecting;
+                        if !dnd_paused {
+                            log_health(&cameras[current_idx].name, HealthEvent::Connecting);
+                            pipeline_wrapper = share.open(config, &cameras[current_idx], convert[current_idx])?;
+                        } else if let Err(e) = pipeline_wrapper.resume() {
+                            // A session the camera dropped meanwhile surfaces here or
+                            // as a bus error, and reconnects like any other failure
+                            eprintln!("Pipeline: {} could not resume: {}", cameras[current_idx].name, e);
+                            pipeline_wrapper.abandon();
+                            reconnect_at = Some(now + RECONNECT_DELAY);
+                            state = ConnectionState::Reconnecting;
+                        }
+                        dwell_timer.restart(now);
+                    }
+                    dnd_paused = false;
+                    shown_state = None;
+                    shown_bar = None;
+                }
+                events.emit(Event::Dnd { active: dnd });
+            }
+        }
+
+        // Wait out a doorbell hold rather than blanking the screen while someone is at the door
+        if max_runtime.is_some_and(|max| started.elapsed() >= max) && doorbell_hold.is_none() {
+            println!("Restart: Max runtime reached, exiting for restart");
+            break;
+        }
+
+        // PiP runs while it adds something: not the main camera, and nothing covering the video
+        let want_pip = pip_idx.filter(|&i| i != current_idx && !dnd && !overview_open && !keypad_open);
+        if pip.as_ref().map(|(index, _)| *index) != want_pip {
+            if let Some((_, old)) = pip.take() {
+                old.abandon();
+            }
+            pip_frame.clear();
+            pip_retry_at = None;
+        }
+        if let (None, Some(index), Some((layout, _))) = (&pip, want_pip, pip_area) {
+            if pip_retry_at.is_none_or(|t| now >= t) {
+                match open_camera_sized(config, &cameras[index], layout.width, layout.height, convert[index]) {
+                    Ok(pipeline) => pip = Some((index, pipeline)),
+                    Err(e) => {
+                        eprintln!("PiP: {} failed to open: {}", cameras[index].name, e);
+                        pip_retry_at = Some(now + RECONNECT_DELAY);
+                    }
+                }
+            }
+        }
+        let mut pip_failure = None;
+        if let (Some((index, pipeline)), Some((layout, origin))) = (&pip, pip_area) {
+            if let Some(sample) = pipeline.sink.try_pull_sample(gstreamer::ClockTime::ZERO) {
+                let frame = sample.buffer().zip(sample.caps().and_then(FrameLayout::from_caps));
+                match frame.map(|(buffer, got)| (got.mismatch(&layout, buffer.size()), buffer.map_readable())) {
+                    Some((None, Ok(map))) => {
+                        idle = false;
+                        pip_frame.clear();
+                        pip_frame.extend_from_slice(&map[..layout.stride as usize * layout.height as usize]);
+                        fb.blit_at(&pip_frame, layout, origin);
+                    }
+                    Some((Some(reason), _)) => pip_failure = Some(format!("Unexpected frame layout: {}", reason)),
+                    _ => pip_failure = Some("sample without a readable buffer or video caps".to_string()),
+                }
+            }
+            pip_failure = pip_failure.or_else(|| pipeline.poll_error());
+            if let Some(message) = &pip_failure {
+                eprintln!("PiP: {} error: {}", cameras[*index].name, message);
+            }
+        }
+        if pip_failure.is_some() {
+            if let Some((_, failed)) = pip.take() {
+                failed.abandon();
+            }
+            pip_retry_at = Some(now + RECONNECT_DELAY);
+        }
+
+        // Overview in place of the video, redrawn now and then for the ages
+        if let Some(tiles) = overview.as_ref().filter(|_| overview_open && !dnd) {
+            if overview_drawn.is_none_or(|t| now.saturating_duration_since(t) >= OVERVIEW_REDRAW) {
+                tiles.draw(&mut fb, cameras, current_idx, state, name_style, Instant::now());
+                overview_drawn = Some(now);
+            }
+        }
+
+        // Keypad over whatever was last on screen, redrawn when its entry changes
+        if let Some(pad) = keypad.as_ref().filter(|_| keypad_open && !keypad_drawn) {
+            pad.draw(&mut fb, camera_button_fill, name_style);
+            keypad_drawn = true;
+        }
+
+        // 4. Status screen while there is no video, drawn once per state change
+        if !dnd && !overview_open && !keypad_open && shown_state != Some(state) {
+            spinner = None;
+            let message = match state {
+                ConnectionState::Connecting => Some(&config.messages.connecting),
+                ConnectionState::Reconnecting => Some(&config.messages.reconnecting),
+                ConnectionState::Live => None,
+            };
+            if let Some(message) = message {
+                let name = &cameras[current_idx].name;
+                let held_frame = !last_frame.is_empty() && last_frame_camera == current_idx;
+                if state == ConnectionState::Reconnecting && held_frame && config.reconnect_display(&cameras[current_idx]) == ReconnectDisplay::LastFrame {
+                    // Frozen picture with only the camera name back on top
+                    fb.blit(&last_frame, video_layout, video_area.top);
+                    if config.shows_layer(&cameras[current_idx], Layer::Name) {
+                        let anchor = config.name_anchor(&cameras[current_idx]);
+                        let style = MonoTextStyle::new(name_style.font, config.name_color(&cameras[current_idx]).0);
+                        overlay::draw_anchored_text(&mut fb.blended(config.overlay_alpha), &ctx, name, style, anchor);
+                    }
+                } else if state == ConnectionState::Connecting && config.hold_last_frame && !last_frame.is_empty() {
+                    // Previous camera's picture, dimmed, instead of a black flash
+                    fb.blit_dimmed(&last_frame, video_layout, video_area.top, HELD_FRAME_BRIGHTNESS);
+                    overlay::draw_status_text(&mut fb, &ctx, name, message, name_style);
+                } else {
+                    overlay::draw_status_screen(&mut fb, &ctx, name, message, name_style);
+                    shown_bar = None;
+                    if let Some(settings) = &config.spinner {
+                        spinner = Some((overlay::spinner_area(&ctx, message, name_style.font, settings.size), now, None));
+                    }
+                }
+            }
+            shown_state = Some(state);
+        }
+        // Turns by the clock, so a slow loop skips steps instead of slowing down
+        if let (Some(settings), Some((area, since, drawn))) = (&config.spinner, spinner.as_mut().filter(|_| !dnd && !overview_open && !keypad_open)) {
+            let step = overlay::spinner_step(now.saturating_duration_since(*since));
+            if *drawn != Some(step) {
+                let color = settings.color.unwrap_or(config.theme.text).0;
+                overlay::draw_spinner(&mut fb, &ctx, *area, settings.width, color, step);
+                *drawn = Some(step);
+            }
+        }
+
+        // 5. Status bar, outside the video area so only redrawn when its content changes
+        if let Some(bar) = config.status_bar.as_ref().filter(|_| !dnd) {
+            let text = overlay::status_bar_text(bar, &ctx, &cameras[current_idx].name, current_idx, cameras.len());
+            let content = (text, state);
+            if shown_bar.as_ref() != Some(&content) {
+                overlay::draw_status_bar(&mut fb, &ctx, bar, &content.0, state, name_style);
+                shown_bar = Some(content);
+            }
+        }
+    }
+
+    if let Some((_, pipeline)) = pip {
+        pipeline.abandon();
+    }
+    pipeline_wrapper.stop()?;
+    Ok(())
+}
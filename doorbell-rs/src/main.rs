@@ -1,253 +1,216 @@
-use anyhow::{Context, Result, anyhow};
-use embedded_graphics::{
-    mono_font::{ascii::FONT_10X20, MonoTextStyle},
-    pixelcolor::Rgb888,
-    prelude::*,
-    primitives::{PrimitiveStyle, Triangle},
-    text::Text,
-};
-use evdev::{Device, InputEventKind, Key};
-use gstreamer::prelude::*;
-use gstreamer_app::AppSink;
-use serde::Deserialize;
-use std::fs::{self, File};
-use std::io::Read;
+use anyhow::{bail, Context, Result};
+use doorbell_rs::config::{is_remote, BenchConfig};
+use doorbell_rs::Config;
+use std::env;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
-
-// --- Configuration ---
-#[derive(Debug, Deserialize, Clone)]
-struct CameraConfig {
-    name: String,
-    url: String,
-    #[serde(default)]
-    comment: String,
-}
+use std::time::Duration;
+
+const DEFAULT_CONFIG: &str = "feeds.json";
+const DEFAULT_CONFIG_CACHE: &str = "feeds.cache.json";
+const RELOAD_POLL: Duration = Duration::from_millis(200);
 
-// --- Constants ---
-const AUTO_CYCLE_SECONDS: u64 = 1800; // 30 minutes
-const FRAME_WIDTH: u32 = 800;
-const FRAME_HEIGHT: u32 = 480;
-
-// --- Framebuffer ---
-struct Framebuffer {
-    mem: memmap2::MmapMut,
-    width: u32,
-    height: u32,
-    stride: u32,
+// Set by the SIGHUP handler, which can't safely do more than that
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sighup(_: libc::c_int) {
+    RELOAD_REQUESTED.store(true, Ordering::Relaxed);
 }
 
-impl Framebuffer {
-    fn new(path: &str) -> Result<Self> {
-        let file = File::options().read(true).write(true).open(path)
-            .context("Failed to open framebuffer device")?;
-        
-        // In production, use `ioctl` (FBIOGET_VSCREENINFO) to populate these.
-        // For Pi Touchscreen, 800x480 is standard.
-        let width = FRAME_WIDTH;
-        let height = FRAME_HEIGHT;
-        let bpp = 32;
-        let stride = width * (bpp / 8);
-        
-        let mem = unsafe { memmap2::MmapMut::map_mut(&file)? };
-
-        Ok(Self { mem, width, height, stride })
-    }
+#[derive(Default, Clone)]
+struct Args {
+    config: Option<String>,
+    config_dir: Option<String>,
+    config_cache: Option<String>,
+    setup: bool,
+    debug_input: bool,
+    force_software_decode: bool,
+    check_config: bool,
+    bench: Option<u64>,
+    bench_file: Option<String>,
 }
 
-impl DrawTarget for Framebuffer {
-    type Color = Rgb888;
-    type Error = core::convert::Infallible;
-
-    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
-    where
-        I: IntoIterator<Item = Pixel<Self::Color>>,
-    {
-        for Pixel(coord, color) in pixels.into_iter() {
-            if coord.x >= 0 && coord.x < self.width as i32 && coord.y >= 0 && coord.y < self.height as i32 {
-                let offset = (coord.y as usize * self.stride as usize) + (coord.x as usize * 4);
-                if offset + 4 <= self.mem.len() {
-                    // BGRA
-                    self.mem[offset] = color.b;
-                    self.mem[offset + 1] = color.g;
-                    self.mem[offset + 2] = color.r;
-                    self.mem[offset + 3] = 255;
-                }
+fn parse_args() -> Result<Args> {
+    let mut args = Args::default();
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--config" => args.config = Some(iter.next().context("--config requires a path")?),
+            "--config-dir" => args.config_dir = Some(iter.next().context("--config-dir requires a path")?),
+            "--config-cache" => args.config_cache = Some(iter.next().context("--config-cache requires a path")?),
+            "--setup" => args.setup = true,
+            "--debug-input" => args.debug_input = true,
+            "--force-software-decode" => args.force_software_decode = true,
+            "--check-config" => args.check_config = true,
+            "--bench" => {
+                let frames = iter.next().context("--bench requires a frame count")?;
+                args.bench = Some(frames.parse().with_context(|| format!("Invalid --bench frame count: {}", frames))?);
             }
+            "--bench-file" => args.bench_file = Some(iter.next().context("--bench-file requires a path")?),
+            other => bail!("Unknown argument: {}", other),
         }
-        Ok(())
     }
+    if args.bench_file.is_some() && args.bench.is_none() {
+        bail!("--bench-file requires --bench");
+    }
+    Ok(args)
 }
 
-impl OriginDimensions for Framebuffer {
-    fn size(&self) -> Size {
-        Size::new(self.width, self.height)
+// Hides `user:pass@` so summaries can be pasted into tickets or CI logs
+fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else { return url.to_string() };
+    let authority = rest.split('/').next().unwrap_or(rest);
+    match authority.rfind('@') {
+        Some(at) => format!("{}://***@{}", scheme, &rest[at + 1..]),
+        None => url.to_string(),
     }
 }
 
-// --- Input Helper ---
-fn spawn_input_thread(events: Arc<Mutex<Vec<bool>>>) {
-    thread::spawn(move || {
-        let mut device_path = None;
-        // Simple discovery
-        if let Ok(dir) = fs::read_dir("/dev/input") {
-            for entry in dir.flatten() {
-                if let Ok(dev) = Device::open(entry.path()) {
-                    let name = dev.name().unwrap_or("").to_lowercase();
-                    if name.contains("touch") || name.contains("ads7846") || name.contains("waveshare") {
-                        println!("Input: Found {}", name);
-                        device_path = Some(entry.path());
-                        break;
-                    }
-                }
-            }
-        }
+fn print_summary(config: &Config) -> Result<()> {
+    if config.cameras.is_empty() {
+        bail!("No cameras defined");
+    }
+    println!("Config OK: {} camera(s)", config.cameras.len());
+    for (i, camera) in config.cameras.iter().enumerate() {
+        let source = match &camera.pipeline {
+            Some(_) => "custom pipeline".to_string(),
+            None => format!("{}, {:?}", redact_url(&camera.url), camera.codec),
+        };
+        println!("  {}: {} ({}, dwell {}s)", i, camera.name, source, config.dwell(camera).as_secs());
+    }
+    println!("Startup camera: {}", config.cameras[config.startup_index()].name);
+    for (i, display) in config.displays.iter().enumerate() {
+        let cameras = config.for_display(display, i == 0)?.cameras;
+        let names: Vec<_> = cameras.iter().map(|c| c.name.as_str()).collect();
+        println!("Display {}: {}", display.framebuffer, names.join(", "));
+    }
+    Ok(())
+}
 
-        if let Some(path) = device_path {
-            if let Ok(mut dev) = Device::open(path) {
-                loop {
-                    // fetch_events blocks by default
-                    if let Ok(iter) = dev.fetch_events() {
-                        for ev in iter {
-                            if ev.kind() == InputEventKind::Key && ev.code() == Key::BTN_TOUCH.code() && ev.value() == 0 {
-                                // Touch Release -> Click
-                                if let Ok(mut q) = events.lock() {
-                                    q.push(true);
-                                }
-                            }
-                        }
-                    } else {
-                        // Device error or disconnect
-                        thread::sleep(Duration::from_secs(1));
-                    }
-                }
+// Re-fetches a remote config into the cache. A changed copy, which has
+// validated by then, is applied through the same path as a SIGHUP reload.
+fn spawn_refresh_thread(url: String, cache: String, interval: Duration) {
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        match Config::refresh_cache(&url, &cache) {
+            Ok(true) => {
+                println!("Config: Refreshed {} from {}, reloading", cache, url);
+                RELOAD_REQUESTED.store(true, Ordering::Relaxed);
             }
-        } else {
-            eprintln!("Input: No touch device found.");
+            Ok(false) => println!("Config: {} is unchanged", url),
+            Err(e) => eprintln!("Config: Refresh failed, keeping {}: {}", cache, e),
         }
     });
 }
 
-// --- Pipeline Helper ---
-struct VideoPipeline {
-    pipeline: gstreamer::Pipeline,
-    sink: AppSink,
+// On SIGHUP, re-reads and validates the config. A good one is handed over
+// through `pending` and `running` is cleared so the display restarts with
+// it; a bad one is logged and the running config stays.
+fn spawn_reload_thread(args: Args, running: Arc<AtomicBool>, pending: Arc<Mutex<Option<Config>>>) {
+    thread::spawn(move || loop {
+        thread::sleep(RELOAD_POLL);
+        if !RELOAD_REQUESTED.swap(false, Ordering::Relaxed) {
+            continue;
+        }
+        println!("Config: Reload requested");
+        match load_config(&args) {
+            Ok(config) if config.cameras.is_empty() => eprintln!("Config: Reload rejected, keeping the current config: no cameras defined"),
+            Ok(config) => {
+                println!("Config: Reloaded {} camera(s) from {}", config.cameras.len(), config_source(&args));
+                *pending.lock().unwrap() = Some(apply_args(config, &args));
+                running.store(false, Ordering::Relaxed);
+            }
+            Err(e) => eprintln!("Config: Reload rejected, keeping the current config: {}", e),
+        }
+    });
 }
 
-impl VideoPipeline {
-    fn new(url: &str) -> Result<Self> {
-        // Hardware accelerated decoding for Pi (v4l2h264dec)
-        // Force BGRA for Framebuffer
-        let pipeline_str = format!(
-            "rtspsrc location={} latency=0 protocols=tcp ! rtph264depay ! h264parse ! v4l2h264dec ! videoconvert ! video/x-raw,format=BGRA,width={},height={} ! appsink name=sink drop=true max-buffers=1",
-            url, FRAME_WIDTH, FRAME_HEIGHT
-        );
-        
-        let pipeline = gstreamer::parse_launch(&pipeline_str)?
-            .downcast::<gstreamer::Pipeline>()
-            .map_err(|_| anyhow!("Not a pipeline"))?;
-
-        let sink = pipeline.by_name("sink").context("No sink")?
-            .downcast::<AppSink>()
-            .map_err(|_| anyhow!("Not an AppSink"))?;
-
-        pipeline.set_state(gstreamer::State::Playing)?;
-        Ok(Self { pipeline, sink })
+// Command line switches win over the file, at startup and on every reload
+fn apply_args(mut config: Config, args: &Args) -> Config {
+    config.setup_mode |= args.setup;
+    config.debug_input |= args.debug_input;
+    config.force_software_decode |= args.force_software_decode;
+    if let Some(frames) = args.bench {
+        config = config.for_bench(BenchConfig { frames, file: args.bench_file.clone() });
     }
+    config
+}
 
-    fn stop(&self) -> Result<()> {
-        self.pipeline.set_state(gstreamer::State::Null)?;
-        Ok(())
-    }
+// Where the config comes from, as given on the command line
+fn config_source(args: &Args) -> &str {
+    args.config_dir.as_deref().or(args.config.as_deref()).unwrap_or(DEFAULT_CONFIG)
 }
 
-// --- Main ---
-fn main() -> Result<()> {
-    gstreamer::init()?;
-
-    let config_path = "feeds.json";
-    let cameras: Vec<CameraConfig> = serde_json::from_reader(
-        File::open(config_path).context("Could not open feeds.json")?
-    )?;
-    if cameras.is_empty() { return Err(anyhow!("No cameras defined")); }
-
-    let mut fb = match Framebuffer::new("/dev/fb0") {
-        Ok(fb) => fb,
-        Err(e) => {
-            eprintln!("Error opening FB: {}", e);
-            // Panic or loop? simplified for now:
-            return Err(e);
+fn load_config(args: &Args) -> Result<Config> {
+    let config = match &args.config_dir {
+        Some(dir) => {
+            // Global settings still come from feeds.json when present
+            let path = args.config.as_deref()
+                .or_else(|| Path::new(DEFAULT_CONFIG).exists().then_some(DEFAULT_CONFIG));
+            Config::load_with_dir(path, dir)?
         }
+        None => match args.config.as_deref().unwrap_or(DEFAULT_CONFIG) {
+            url if is_remote(url) => Config::load_remote(url, args.config_cache.as_deref().unwrap_or(DEFAULT_CONFIG_CACHE))?,
+            // First boot: show setup instructions until the file is written
+            path if !Path::new(path).exists() && !args.check_config => Config::empty(),
+            path => Config::load(path)?,
+        },
     };
+    Ok(config)
+}
 
-    let touch_queue = Arc::new(Mutex::new(Vec::new()));
-    spawn_input_thread(touch_queue.clone());
-
-    let running = Arc::new(Mutex::new(true));
-    let r = running.clone();
-    ctrlc::set_handler(move || { *r.lock().unwrap() = false; })?;
-
-    let mut current_idx = 0;
-    let mut pipeline_wrapper = VideoPipeline::new(&cameras[current_idx].url)?;
-    let mut last_interaction = Instant::now();
-
-    let name_style = MonoTextStyle::new(&FONT_10X20, Rgb888::WHITE);
-    let tri_style = PrimitiveStyle::with_fill(Rgb888::new(200, 200, 200));
-
-    println!("Starting loop for camera: {}", cameras[current_idx].name);
-
-    while *running.lock().unwrap() {
-        // 1. Check Video
-        if let Some(sample) = pipeline_wrapper.sink.try_pull_sample(gstreamer::ClockTime::from_mseconds(10)) {
-            let buffer = sample.buffer().context("No buffer")?;
-            let map = buffer.map_readable()?;
-            
-            // Blit to FB
-            let len = fb.mem.len().min(map.len());
-            fb.mem[..len].copy_from_slice(&map[..len]);
-            drop(map);
-
-            // 2. Draw Overlay
-            let name = &cameras[current_idx].name;
-            // Center text roughly
-            let text_width = name.len() as u32 * 10;
-            let text_x = (FRAME_WIDTH - text_width) / 2;
-            Text::new(name, Point::new(text_x as i32, 30), name_style).draw(&mut fb).ok();
-
-            // Draw Arrows
-            Triangle::new(Point::new(10, 240), Point::new(60, 210), Point::new(60, 270))
-                .into_styled(tri_style).draw(&mut fb).ok();
-            Triangle::new(Point::new(790, 240), Point::new(740, 210), Point::new(740, 270))
-                .into_styled(tri_style).draw(&mut fb).ok();
-        }
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    let mut config = load_config(&args)?;
 
-        // 3. Logic: Check for Click or Timeout
-        let mut switch_cam = false;
-        {
-            let mut q = touch_queue.lock().unwrap();
-            if !q.is_empty() {
-                q.clear();
-                switch_cam = true;
-                last_interaction = Instant::now();
-                println!("Touch detected!");
-            }
-        }
+    if args.check_config {
+        return print_summary(&config);
+    }
+    config = apply_args(config, &args);
+    if let (Some(url), Some(seconds)) = (args.config.as_deref().filter(|c| is_remote(c)), config.config_refresh_seconds) {
+        let cache = args.config_cache.as_deref().unwrap_or(DEFAULT_CONFIG_CACHE);
+        spawn_refresh_thread(url.to_string(), cache.to_string(), Duration::from_secs(seconds));
+    }
 
-        if last_interaction.elapsed().as_secs() > AUTO_CYCLE_SECONDS {
-            switch_cam = true;
-            last_interaction = Instant::now();
-            println!("Auto-cycling...");
-        }
+    // Installed once here rather than in run(), which may be called repeatedly
+    let running = Arc::new(AtomicBool::new(true));
+    // Tells a stop for good apart from one for a reload
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let (r, s) = (running.clone(), shutdown.clone());
+    ctrlc::set_handler(move || {
+        s.store(true, Ordering::Relaxed);
+        r.store(false, Ordering::Relaxed);
+    })
+    .context("Could not install signal handler")?;
+    // ctrlc's `termination` feature also takes SIGHUP as a shutdown. Installing
+    // ours afterwards replaces that handler, so this must stay after set_handler.
+    if unsafe { libc::signal(libc::SIGHUP, on_sighup as *const () as libc::sighandler_t) } == libc::SIG_ERR {
+        bail!("Could not install SIGHUP handler");
+    }
 
-        if switch_cam {
-            pipeline_wrapper.stop()?;
-            current_idx = (current_idx + 1) % cameras.len();
-            println!("Switching to: {}", cameras[current_idx].name);
-            pipeline_wrapper = VideoPipeline::new(&cameras[current_idx].url)?;
+    if config.cameras.is_empty() {
+        let shown_path = match env::current_dir() {
+            Ok(dir) if !is_remote(config_source(&args)) => dir.join(config_source(&args)).display().to_string(),
+            _ => config_source(&args).to_string(),
+        };
+        let reload = || load_config(&args).map_err(|e| eprintln!("Setup: {}", e)).ok();
+        match doorbell_rs::wait_for_cameras(&config, &shown_path, &running, reload)? {
+            Some(loaded) => config = apply_args(loaded, &args),
+            None => return Ok(()),
         }
     }
 
-    pipeline_wrapper.stop()?;
+    // Only from here on, so a reload can't end the setup screen; a SIGHUP before stays pending
+    let pending = Arc::new(Mutex::new(None));
+    spawn_reload_thread(args.clone(), running.clone(), pending.clone());
+    loop {
+        doorbell_rs::run(&config, &running)?;
+        let Some(reloaded) = pending.lock().unwrap().take().filter(|_| !shutdown.load(Ordering::Relaxed)) else { break };
+        println!("Config: Restarting with the reloaded config");
+        config = reloaded;
+        running.store(true, Ordering::Relaxed);
+    }
     Ok(())
 }
@@ -0,0 +1,714 @@
+use crate::clock::{local_hhmm, Clock};
+use crate::config::{Anchor, BackgroundPattern, BarPosition, ButtonsConfig, Config, Insets, Layer, LogoConfig, PipConfig, StatusBarConfig, StatusItem, Theme, TouchMissing};
+use crate::framebuffer::{FrameLayout, Framebuffer};
+use crate::pipeline::ConnectionState;
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{Arc, Circle, Line, Polyline, PrimitiveStyle, Rectangle, RoundedRectangle, Triangle},
+    text::{Baseline, Text},
+};
+use image::imageops::FilterType;
+use std::time::Duration;
+
+pub const TEXT_MARGIN: u32 = 20; // padding between overlay text and the safe area edge
+pub const PROGRESS_BAR_HEIGHT: u32 = 4;
+
+// --- Render context ---
+// Live screen geometry and look, borrowed by the overlay drawing functions
+pub struct RenderContext<'a> {
+    pub layout: FrameLayout,
+    pub safe_area: Insets,
+    pub theme: &'a Theme,
+    pub clock: &'a dyn Clock,
+}
+
+impl RenderContext<'_> {
+    pub fn size(&self) -> Size {
+        Size::new(self.layout.width, self.layout.height)
+    }
+
+    // Screen area inside the safe-area insets
+    pub fn safe_rect(&self) -> Rectangle {
+        let inset = self.safe_area;
+        Rectangle::new(
+            Point::new(inset.left as i32, inset.top as i32),
+            Size::new(
+                self.layout.width.saturating_sub(inset.left + inset.right),
+                self.layout.height.saturating_sub(inset.top + inset.bottom),
+            ),
+        )
+    }
+
+    // Widest a line of overlay text may be
+    pub fn text_width(&self) -> u32 {
+        self.safe_rect().size.width.saturating_sub(2 * TEXT_MARGIN)
+    }
+
+    // A `size` box attached to `anchor` inside the safe area, `margin` from its edges
+    fn anchored(&self, size: Size, anchor: Anchor, margin: u32) -> Rectangle {
+        let safe = self.safe_rect();
+        let (x, y) = anchor.position(size.width, size.height, safe.size.width, safe.size.height, margin);
+        Rectangle::new(safe.top_left + Point::new(x, y), size)
+    }
+}
+
+// Number of glyphs that fit in `max_width` pixels, accounting for spacing between glyphs
+fn chars_per_line(font: &MonoFont, max_width: u32) -> usize {
+    let advance = font.character_size.width + font.character_spacing;
+    ((max_width + font.character_spacing) / advance).max(1) as usize
+}
+
+fn line_width(font: &MonoFont, line: &str) -> u32 {
+    let n = line.chars().count() as u32;
+    if n == 0 {
+        return 0;
+    }
+    n * font.character_size.width + (n - 1) * font.character_spacing
+}
+
+// Splits text on whitespace into lines no wider than `max_width` pixels.
+// Words longer than a whole line are broken mid-word.
+pub fn wrap_text(text: &str, font: &MonoFont, max_width: u32) -> Vec<String> {
+    let max_chars = chars_per_line(font, max_width);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word = word;
+        while word.chars().count() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let split = word.char_indices().nth(max_chars).map(|(i, _)| i).unwrap_or(word.len());
+            lines.push(word[..split].to_string());
+            word = &word[split..];
+        }
+
+        let line_len = line.chars().count();
+        let word_len = word.chars().count();
+        if !line.is_empty() && line_len + 1 + word_len > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// Draws wrapped text centered horizontally, first baseline at `top`, one font height per line
+pub fn draw_wrapped_text<D>(target: &mut D, text: &str, style: MonoTextStyle<'_, Rgb888>, top: i32, max_width: u32)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let font = style.font;
+    let target_width = target.bounding_box().size.width;
+    let mut y = top;
+    for line in wrap_text(text, font, max_width) {
+        let x = target_width.saturating_sub(line_width(font, &line)) / 2;
+        Text::new(&line, Point::new(x as i32, y), style).draw(target).ok();
+        y += font.character_size.height as i32;
+    }
+}
+
+// Area covered by draw_wrapped_text with the same arguments
+pub fn wrapped_text_rect(text: &str, font: &MonoFont, top: i32, max_width: u32, target_width: u32) -> Rectangle {
+    let lines = wrap_text(text, font, max_width);
+    let block_width = lines.iter().map(|line| line_width(font, line)).max().unwrap_or(0);
+    let left = target_width.saturating_sub(block_width) / 2;
+    // `top` is the first baseline, the glyphs reach above it
+    let size = Size::new(block_width, font.character_size.height * lines.len() as u32);
+    Rectangle::new(Point::new(left as i32, top - font.baseline as i32), size)
+}
+
+// Area covered by draw_anchored_text with the same arguments
+pub fn anchored_text_rect(ctx: &RenderContext, text: &str, font: &MonoFont, anchor: Anchor) -> Rectangle {
+    let lines = wrap_text(text, font, ctx.text_width());
+    let block_width = lines.iter().map(|line| line_width(font, line)).max().unwrap_or(0);
+    let size = Size::new(block_width, font.character_size.height * lines.len() as u32);
+    ctx.anchored(size, anchor, TEXT_MARGIN)
+}
+
+// Draws wrapped text as a block attached to `anchor` inside the safe area,
+// lines aligned to the anchored side
+pub fn draw_anchored_text<D>(target: &mut D, ctx: &RenderContext, text: &str, style: MonoTextStyle<'_, Rgb888>, anchor: Anchor)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let font = style.font;
+    let lines = wrap_text(text, font, ctx.text_width());
+    let line_height = font.character_size.height;
+    let block = anchored_text_rect(ctx, text, font, anchor);
+    let (block_width, x0, y0) = (block.size.width, block.top_left.x, block.top_left.y);
+
+    for (i, line) in lines.iter().enumerate() {
+        let slack = (block_width - line_width(font, line)) as i32;
+        let x = match anchor {
+            Anchor::TopLeft | Anchor::BottomLeft => x0,
+            Anchor::TopCenter | Anchor::BottomCenter => x0 + slack / 2,
+            Anchor::TopRight | Anchor::BottomRight => x0 + slack,
+        };
+        let y = y0 + (i as u32 * line_height) as i32;
+        Text::with_baseline(line, Point::new(x, y), style, Baseline::Top).draw(target).ok();
+    }
+}
+
+// Full-screen notice shown while a camera has no video
+pub fn draw_status_screen<D>(target: &mut D, ctx: &RenderContext, camera: &str, message: &str, style: MonoTextStyle<'_, Rgb888>)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    draw_background(target, ctx);
+    draw_status_text(target, ctx, camera, message, style);
+}
+
+// Positions per turn of the spinner, which turns once a second
+const SPINNER_STEPS: u32 = 12;
+const SPINNER_SWEEP_DEGREES: f32 = 270.0;
+
+// Step the spinner should be at, `elapsed` since it appeared
+pub fn spinner_step(elapsed: Duration) -> u32 {
+    (elapsed.as_millis() * SPINNER_STEPS as u128 / 1000 % SPINNER_STEPS as u128) as u32
+}
+
+// Square under the status screen's `message`, as drawn by draw_status_text
+pub fn spinner_area(ctx: &RenderContext, message: &str, font: &MonoFont, size: u32) -> Rectangle {
+    let lines = wrap_text(message, font, ctx.text_width()).len().max(1) as u32;
+    let top = ctx.safe_rect().center().y + ((lines - 1) * font.character_size.height + TEXT_MARGIN) as i32;
+    Rectangle::new(Point::new(ctx.safe_rect().center().x - size as i32 / 2, top), Size::new(size, size))
+}
+
+// Spinner at `step` of SPINNER_STEPS, over the status screen background
+pub fn draw_spinner<D>(target: &mut D, ctx: &RenderContext, area: Rectangle, width: u32, color: Rgb888, step: u32)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    // Restoring the background erases the previous step, pattern included
+    draw_background(&mut target.clipped(&area), ctx);
+    let start = Angle::from_degrees((step % SPINNER_STEPS) as f32 * 360.0 / SPINNER_STEPS as f32);
+    let diameter = area.size.width.saturating_sub(width);
+    Arc::with_center(area.center(), diameter, start, Angle::from_degrees(SPINNER_SWEEP_DEGREES))
+        .into_styled(PrimitiveStyle::with_stroke(color, width))
+        .draw(target)
+        .ok();
+}
+
+const PATTERN_SPACING: i32 = 24;
+
+// Theme background and pattern over the whole screen
+pub fn draw_background<D>(target: &mut D, ctx: &RenderContext)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let background = ctx.theme.background.0;
+    target.clear(background).ok();
+    let shade = pattern_shade(background, ctx.theme.text.0);
+    let size = ctx.size();
+    match ctx.theme.pattern {
+        BackgroundPattern::None => {}
+        BackgroundPattern::Dots => {
+            let dot = PrimitiveStyle::with_fill(shade);
+            for y in (PATTERN_SPACING / 2..size.height as i32).step_by(PATTERN_SPACING as usize) {
+                for x in (PATTERN_SPACING / 2..size.width as i32).step_by(PATTERN_SPACING as usize) {
+                    Rectangle::new(Point::new(x, y), Size::new(2, 2)).into_styled(dot).draw(target).ok();
+                }
+            }
+        }
+        BackgroundPattern::Stripes => {
+            // Diagonals from the top or left edge down to the right
+            let stroke = PrimitiveStyle::with_stroke(shade, 1);
+            let (width, height) = (size.width as i32, size.height as i32);
+            for start in (-height..width).step_by(PATTERN_SPACING as usize * 2) {
+                Line::new(Point::new(start, 0), Point::new(start + height, height)).into_styled(stroke).draw(target).ok();
+            }
+        }
+    }
+}
+
+// An eighth of the way from the background towards the text color: visible, but
+// well below the text
+fn pattern_shade(background: Rgb888, text: Rgb888) -> Rgb888 {
+    let mix = |b: u8, t: u8| (b as i16 + (t as i16 - b as i16) / 8) as u8;
+    Rgb888::new(mix(background.r(), text.r()), mix(background.g(), text.g()), mix(background.b(), text.b()))
+}
+
+// Camera name and message of the status screen, over whatever is on screen
+pub fn draw_status_text<D>(target: &mut D, ctx: &RenderContext, camera: &str, message: &str, style: MonoTextStyle<'_, Rgb888>)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    draw_wrapped_text(target, camera, style, ctx.safe_rect().top_left.y + 30, ctx.text_width());
+    let middle = ctx.safe_rect().center().y;
+    draw_wrapped_text(target, message, style, middle, ctx.text_width());
+}
+
+// Centered block of text, one wrapped paragraph per line of `text`
+pub fn draw_paragraphs<D>(target: &mut D, ctx: &RenderContext, text: &str, style: MonoTextStyle<'_, Rgb888>)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let line_height = style.font.character_size.height;
+    let paragraphs: Vec<_> = text.lines().map(|p| wrap_text(p, style.font, ctx.text_width()).len().max(1) as u32).collect();
+    let height = paragraphs.iter().sum::<u32>() * line_height;
+    let safe = ctx.safe_rect();
+    let mut top = safe.center().y - height as i32 / 2;
+    for (paragraph, lines) in text.lines().zip(paragraphs) {
+        draw_wrapped_text(target, paragraph, style, top, ctx.text_width());
+        top += (lines * line_height) as i32;
+    }
+}
+
+// Area of a full progress bar
+pub fn progress_bar_rect(ctx: &RenderContext, height: u32) -> Rectangle {
+    let safe = ctx.safe_rect();
+    let top = safe.top_left.y + safe.size.height as i32 - height as i32;
+    Rectangle::new(Point::new(safe.top_left.x, top), Size::new(safe.size.width, height))
+}
+
+// Bar along the bottom of the safe area, `fraction` (0.0-1.0) of its width filled
+pub fn draw_progress_bar<D>(target: &mut D, ctx: &RenderContext, fraction: f32, height: u32)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let full = progress_bar_rect(ctx, height);
+    let width = (full.size.width as f32 * fraction.clamp(0.0, 1.0)) as u32;
+    if width == 0 {
+        return;
+    }
+    Rectangle::new(full.top_left, Size::new(width, height))
+        .into_styled(PrimitiveStyle::with_fill(ctx.theme.progress.0))
+        .draw(target)
+        .ok();
+}
+
+// --- Status bar ---
+// Text part of the status bar, e.g. "Front door  2/5  14:03"
+pub fn status_bar_text(bar: &StatusBarConfig, ctx: &RenderContext, camera: &str, index: usize, total: usize) -> String {
+    let parts: Vec<String> = bar.items.iter().filter_map(|item| match item {
+        StatusItem::Name => Some(camera.to_string()),
+        StatusItem::Index => Some(format!("{}/{}", index + 1, total)),
+        StatusItem::Time => Some(local_hhmm(ctx.clock)),
+        StatusItem::Connection => None,
+    }).collect();
+    parts.join("  ")
+}
+
+// Opaque strip outside the video area, redrawn only when its content changes
+pub fn draw_status_bar<D>(target: &mut D, ctx: &RenderContext, bar: &StatusBarConfig, text: &str, state: ConnectionState, style: MonoTextStyle<'_, Rgb888>)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let top = match bar.position {
+        BarPosition::Top => 0,
+        BarPosition::Bottom => ctx.layout.height.saturating_sub(bar.height) as i32,
+    };
+    let strip = Rectangle::new(Point::new(0, top), Size::new(ctx.layout.width, bar.height));
+    strip.into_styled(PrimitiveStyle::with_fill(bar.background.0)).draw(target).ok();
+
+    let middle = top + bar.height as i32 / 2;
+    let left = (ctx.safe_area.left + TEXT_MARGIN / 2) as i32;
+    Text::with_baseline(text, Point::new(left, middle), style, Baseline::Middle).draw(target).ok();
+
+    if bar.items.contains(&StatusItem::Connection) {
+        let color = state_color(state);
+        let diameter = (bar.height / 2).max(4);
+        let right = ctx.layout.width.saturating_sub(ctx.safe_area.right + TEXT_MARGIN / 2) as i32;
+        let center = Point::new(right - diameter as i32 / 2, middle);
+        Circle::with_center(center, diameter).into_styled(PrimitiveStyle::with_fill(color)).draw(target).ok();
+    }
+}
+
+// Green, amber or red status dot
+pub fn state_color(state: ConnectionState) -> Rgb888 {
+    match state {
+        ConnectionState::Live => Rgb888::new(0, 200, 0),
+        ConnectionState::Connecting => Rgb888::new(230, 160, 0),
+        ConnectionState::Reconnecting => Rgb888::new(220, 0, 0),
+    }
+}
+
+// --- Navigation buttons ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavButton {
+    Previous,
+    Next,
+}
+
+enum ButtonShape {
+    Triangles,
+    Rounded { corner_radius: u32, fill: Rgb888 },
+}
+
+// Previous/next buttons at the screen edges. Drawing and hit-testing use the
+// same rectangles, so what is visible is exactly what is tappable.
+pub struct NavButtons {
+    shape: ButtonShape,
+    previous: Rectangle,
+    next: Rectangle,
+}
+
+impl NavButtons {
+    // Placed inside the safe area; falls back to the classic triangles when `config` is None
+    pub fn new(config: Option<&ButtonsConfig>, ctx: &RenderContext) -> Self {
+        let safe = ctx.safe_rect();
+        let (size, margin, center_y, shape) = match config {
+            Some(c) => (
+                Size::new(c.width, c.height),
+                c.margin,
+                c.center_y.unwrap_or(safe.center().y),
+                ButtonShape::Rounded { corner_radius: c.corner_radius, fill: c.fill.0 },
+            ),
+            None => (Size::new(51, 61), 10, safe.center().y, ButtonShape::Triangles),
+        };
+        let top = center_y - size.height as i32 / 2;
+        let left = safe.top_left.x + margin as i32;
+        let right = safe.top_left.x + safe.size.width as i32 - (margin + size.width) as i32;
+        let previous = Rectangle::new(Point::new(left, top), size);
+        let next = Rectangle::new(Point::new(right, top), size);
+        Self { shape, previous, next }
+    }
+
+    pub fn rects(&self) -> [Rectangle; 2] {
+        [self.previous, self.next]
+    }
+
+    pub fn hit(&self, point: Point) -> Option<NavButton> {
+        if self.previous.contains(point) {
+            Some(NavButton::Previous)
+        } else if self.next.contains(point) {
+            Some(NavButton::Next)
+        } else {
+            None
+        }
+    }
+
+    pub fn draw<D>(&self, target: &mut D, color: Rgb888)
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        for (rect, pointing_left) in [(self.previous, true), (self.next, false)] {
+            let (left, right) = (rect.top_left.x, rect.top_left.x + rect.size.width as i32 - 1);
+            let (top, bottom) = (rect.top_left.y, rect.top_left.y + rect.size.height as i32 - 1);
+            let mid = rect.center().y;
+            match self.shape {
+                ButtonShape::Triangles => {
+                    let (tip, base) = if pointing_left { (left, right) } else { (right, left) };
+                    Triangle::new(Point::new(tip, mid), Point::new(base, top), Point::new(base, bottom))
+                        .into_styled(PrimitiveStyle::with_fill(color))
+                        .draw(target)
+                        .ok();
+                }
+                ButtonShape::Rounded { corner_radius, fill } => {
+                    RoundedRectangle::with_equal_corners(rect, Size::new(corner_radius, corner_radius))
+                        .into_styled(PrimitiveStyle::with_fill(fill))
+                        .draw(target)
+                        .ok();
+                    // Chevron in the middle third of the button
+                    let center = rect.center();
+                    let half_w = (rect.size.width / 6) as i32;
+                    let half_h = (rect.size.height / 6) as i32;
+                    let dir = if pointing_left { 1 } else { -1 };
+                    let points = [
+                        Point::new(center.x + dir * half_w, center.y - half_h),
+                        Point::new(center.x - dir * half_w, center.y),
+                        Point::new(center.x + dir * half_w, center.y + half_h),
+                    ];
+                    Polyline::new(&points)
+                        .into_styled(PrimitiveStyle::with_stroke(color, 6))
+                        .draw(target)
+                        .ok();
+                }
+            }
+        }
+    }
+}
+
+// --- Camera buttons ---
+const CAMERA_BUTTON_PADDING: u32 = 12; // around the label inside a camera button
+const CAMERA_BUTTON_GAP: u32 = 10;
+
+// Row of labelled buttons along the bottom of the safe area for the current
+// camera's PTZ presets and actions. Buttons that don't fit on the row are left out.
+pub struct CameraButtons {
+    buttons: Vec<(Rectangle, String)>,
+    top: i32,
+}
+
+impl CameraButtons {
+    pub fn new(labels: &[&str], ctx: &RenderContext, font: &MonoFont) -> Self {
+        let safe = ctx.safe_rect();
+        let height = font.character_size.height + 2 * CAMERA_BUTTON_PADDING;
+        let top = safe.top_left.y + safe.size.height as i32 - (height + CAMERA_BUTTON_GAP) as i32;
+        let right_edge = safe.top_left.x + safe.size.width as i32 - TEXT_MARGIN as i32;
+        let mut left = safe.top_left.x + TEXT_MARGIN as i32;
+
+        let mut buttons = Vec::new();
+        for label in labels {
+            let width = line_width(font, label) + 2 * CAMERA_BUTTON_PADDING;
+            if left + width as i32 > right_edge {
+                break;
+            }
+            buttons.push((Rectangle::new(Point::new(left, top), Size::new(width, height)), label.to_string()));
+            left += (width + CAMERA_BUTTON_GAP) as i32;
+        }
+        Self { buttons, top }
+    }
+
+    // Area of the row, None when no button fits
+    pub fn area(&self) -> Option<Rectangle> {
+        let first = self.buttons.first()?.0;
+        let last = self.buttons.last()?.0;
+        Some(Rectangle::with_corners(first.top_left, last.bottom_right().unwrap_or(last.top_left)))
+    }
+
+    // Index of the label whose button contains `point`
+    pub fn hit(&self, point: Point) -> Option<usize> {
+        self.buttons.iter().position(|(rect, _)| rect.contains(point))
+    }
+
+    pub fn draw<D>(&self, target: &mut D, fill: Rgb888, style: MonoTextStyle<'_, Rgb888>)
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        for (rect, label) in &self.buttons {
+            RoundedRectangle::with_equal_corners(*rect, Size::new(8, 8))
+                .into_styled(PrimitiveStyle::with_fill(fill))
+                .draw(target)
+                .ok();
+            // Buttons are sized to their label, so padding on the left centres it
+            let origin = Point::new(rect.top_left.x + CAMERA_BUTTON_PADDING as i32, rect.center().y);
+            Text::with_baseline(label, origin, style, Baseline::Middle).draw(target).ok();
+        }
+    }
+
+    // One line just above the row, e.g. an action's acknowledgement
+    pub fn draw_notice<D>(&self, target: &mut D, ctx: &RenderContext, text: &str, style: MonoTextStyle<'_, Rgb888>)
+    where
+        D: DrawTarget<Color = Rgb888>,
+    {
+        let origin = Point::new(ctx.safe_rect().top_left.x + TEXT_MARGIN as i32, self.top - CAMERA_BUTTON_GAP as i32);
+        Text::with_baseline(text, origin, style, Baseline::Bottom).draw(target).ok();
+    }
+}
+
+// First baseline of the missing touchscreen notice
+pub fn touch_notice_top(ctx: &RenderContext) -> i32 {
+    let safe = ctx.safe_rect();
+    safe.top_left.y + (safe.size.height - 2 * TEXT_MARGIN) as i32
+}
+
+// Baseline start of the latency label, just above the progress bar
+pub fn latency_origin(ctx: &RenderContext) -> Point {
+    let safe = ctx.safe_rect();
+    Point::new(safe.top_left.x + TEXT_MARGIN as i32, safe.top_left.y + (safe.size.height - PROGRESS_BAR_HEIGHT - 8) as i32)
+}
+
+// --- Picture-in-picture ---
+// Where `config`'s PiP window goes on the screen of `ctx`, None without one.
+// `touch` is whether the display takes touch, which decides on the touch notice.
+pub fn pip_window(config: &Config, ctx: &RenderContext, font: &MonoFont, touch: bool) -> Result<Option<Rectangle>, String> {
+    match &config.pip {
+        Some(pip) => pip_rect(ctx, pip, &pip_overlays(config, ctx, font, touch)).map(Some),
+        None => Ok(None),
+    }
+}
+
+// Overlays that can be on screen under the PiP window, with where they are
+// drawn. A swap can put any camera in the main view, so every camera's name
+// and buttons count. The status bar is outside the safe area the window is in.
+fn pip_overlays(config: &Config, ctx: &RenderContext, font: &MonoFont, touch: bool) -> Vec<(String, Rectangle)> {
+    let cameras = &config.cameras;
+    let shown = |layer| cameras.iter().any(|c| config.shows_layer(c, layer));
+    let mut overlays = Vec::new();
+    for camera in cameras.iter().filter(|c| config.shows_layer(c, Layer::Name)) {
+        let rect = anchored_text_rect(ctx, &camera.name, font, config.name_anchor(camera));
+        overlays.push((format!("the name of camera '{}'", camera.name), rect));
+    }
+    for camera in cameras.iter().filter(|c| config.shows_layer(c, Layer::CameraButtons)) {
+        if let Some(area) = CameraButtons::new(&camera.button_labels(), ctx, font).area() {
+            overlays.push((format!("the buttons of camera '{}'", camera.name), area));
+        }
+    }
+    if shown(Layer::Buttons) {
+        let buttons = NavButtons::new(config.buttons.as_ref(), ctx);
+        overlays.extend(buttons.rects().map(|rect| ("the previous/next buttons".to_string(), rect)));
+    }
+    if shown(Layer::ProgressBar) && config.progress_bar && config.auto_cycle {
+        overlays.push(("the progress bar".to_string(), progress_bar_rect(ctx, PROGRESS_BAR_HEIGHT)));
+    }
+    if shown(Layer::TouchNotice) && touch && config.touch_missing == TouchMissing::Notice {
+        let notice = &config.messages.touch_unavailable;
+        let rect = wrapped_text_rect(notice, font, touch_notice_top(ctx), ctx.text_width(), ctx.layout.width);
+        overlays.push(("the touch notice".to_string(), rect));
+    }
+    if shown(Layer::Latency) && config.latency_overlay {
+        // Label as wide as a delay of several seconds
+        let label = Text::new("9999 ms", latency_origin(ctx), MonoTextStyle::new(font, Rgb888::WHITE));
+        overlays.push(("the latency label".to_string(), label.bounding_box()));
+    }
+    // Sized from the image header; a logo that can't be read isn't drawn either
+    let logo = config.logo.as_ref().filter(|_| shown(Layer::Logo));
+    if let Some((logo, (width, height))) = logo.and_then(|l| image::image_dimensions(&l.path).ok().map(|size| (l, size))) {
+        let (width, height) = scaled_size(width, height, logo.width, logo.height);
+        overlays.push(("the logo".to_string(), ctx.anchored(Size::new(width, height), logo.anchor, logo.margin)));
+    }
+    overlays
+}
+
+// Where the PiP window goes in the safe area. It is drawn over everything, so
+// it may take at most half the safe area each way and must miss every overlay
+// in `overlays`, given as a description and the area it is drawn in.
+pub fn pip_rect(ctx: &RenderContext, pip: &PipConfig, overlays: &[(String, Rectangle)]) -> Result<Rectangle, String> {
+    let safe = ctx.safe_rect();
+    let (max_width, max_height) = (safe.size.width / 2, safe.size.height / 2);
+    if pip.width > max_width || pip.height > max_height {
+        return Err(format!("pip size {}x{} is more than half the safe area ({}x{})", pip.width, pip.height, max_width, max_height));
+    }
+    let rect = ctx.anchored(Size::new(pip.width, pip.height), pip.anchor, pip.margin);
+    match overlays.iter().find(|(_, area)| !area.intersection(&rect).is_zero_sized()) {
+        Some((overlay, _)) => Err(format!("pip window at {:?} would cover {}", pip.anchor, overlay)),
+        None => Ok(rect),
+    }
+}
+
+// Setup mode: grid every `spacing` pixels, a centre crosshair and the safe-area outline
+pub fn draw_setup_guides<D>(target: &mut D, ctx: &RenderContext, spacing: u32)
+where
+    D: DrawTarget<Color = Rgb888>,
+{
+    let size = ctx.size();
+    let (w, h) = (size.width as i32, size.height as i32);
+    let color = ctx.theme.guides.0;
+    let thin = PrimitiveStyle::with_stroke(color, 1);
+    let thick = PrimitiveStyle::with_stroke(color, 3);
+
+    for x in (spacing as i32..w).step_by(spacing.max(1) as usize) {
+        Line::new(Point::new(x, 0), Point::new(x, h - 1)).into_styled(thin).draw(target).ok();
+    }
+    for y in (spacing as i32..h).step_by(spacing.max(1) as usize) {
+        Line::new(Point::new(0, y), Point::new(w - 1, y)).into_styled(thin).draw(target).ok();
+    }
+
+    let center = Point::new(w / 2, h / 2);
+    Line::new(center - Point::new(20, 0), center + Point::new(20, 0)).into_styled(thick).draw(target).ok();
+    Line::new(center - Point::new(0, 20), center + Point::new(0, 20)).into_styled(thick).draw(target).ok();
+
+    ctx.safe_rect().into_styled(thick).draw(target).ok();
+}
+
+// --- Logo ---
+// Pre-scaled RGBA image, composited with per-pixel alpha every frame
+pub struct Logo {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    anchor: Anchor,
+    margin: u32,
+}
+
+fn scaled_size(width: u32, height: u32, target_w: Option<u32>, target_h: Option<u32>) -> (u32, u32) {
+    let (w, h) = match (target_w, target_h) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => (w, (height as u64 * w as u64 / width.max(1) as u64) as u32),
+        (None, Some(h)) => ((width as u64 * h as u64 / height.max(1) as u64) as u32, h),
+        (None, None) => (width, height),
+    };
+    (w.max(1), h.max(1))
+}
+
+impl Logo {
+    // Missing or unreadable images are logged and skipped
+    pub fn load(config: &LogoConfig) -> Option<Self> {
+        let img = match image::open(&config.path) {
+            Ok(img) => img,
+            Err(e) => {
+                eprintln!("Logo: Could not load {}: {}", config.path, e);
+                return None;
+            }
+        };
+
+        let (width, height) = scaled_size(img.width(), img.height(), config.width, config.height);
+        let rgba = img.resize_exact(width, height, FilterType::Triangle).to_rgba8().into_raw();
+        println!("Logo: Loaded {} ({}x{})", config.path, width, height);
+
+        Some(Self { width, height, rgba, anchor: config.anchor, margin: config.margin })
+    }
+
+    pub fn draw(&self, fb: &mut Framebuffer, ctx: &RenderContext) {
+        let Point { x: x0, y: y0 } = ctx.anchored(Size::new(self.width, self.height), self.anchor, self.margin).top_left;
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let (fx, fy) = (x0 + x, y0 + y);
+                let src = ((y as u32 * self.width + x as u32) * 4) as usize;
+                let [r, g, b, a] = [self.rgba[src], self.rgba[src + 1], self.rgba[src + 2], self.rgba[src + 3]];
+                fb.blend(fx, fy, Rgb888::new(r, g, b), a);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mono_font::ascii::FONT_10X20;
+
+    #[test]
+    fn spinner_turns_once_a_second() {
+        assert_eq!(spinner_step(Duration::ZERO), 0);
+        assert_eq!(spinner_step(Duration::from_millis(84)), 1);
+        assert_eq!(spinner_step(Duration::from_millis(999)), SPINNER_STEPS - 1);
+        assert_eq!(spinner_step(Duration::from_millis(2500)), SPINNER_STEPS / 2);
+    }
+
+    #[test]
+    fn pattern_shade_leans_slightly_towards_the_text() {
+        assert_eq!(pattern_shade(Rgb888::BLACK, Rgb888::WHITE), Rgb888::new(31, 31, 31));
+        assert_eq!(pattern_shade(Rgb888::new(16, 40, 80), Rgb888::new(16, 0, 160)), Rgb888::new(16, 35, 90));
+        assert_eq!(pattern_shade(Rgb888::WHITE, Rgb888::BLACK), Rgb888::new(224, 224, 224));
+    }
+
+    #[test]
+    fn wraps_to_expected_line_count() {
+        // 100px fits exactly ten 10px glyphs
+        let lines = wrap_text("Front Door Camera By The Garage", &FONT_10X20, 100);
+        assert_eq!(lines, vec!["Front Door", "Camera By", "The Garage"]);
+    }
+
+    #[test]
+    fn breaks_words_longer_than_a_line() {
+        let lines = wrap_text("Backyard-Northeast-Corner", &FONT_10X20, 100);
+        assert_eq!(lines, vec!["Backyard-N", "ortheast-C", "orner"]);
+    }
+
+    #[test]
+    fn pip_fits_the_safe_area_and_misses_the_overlays() {
+        use crate::clock::SystemClock;
+        use crate::config::CameraRef;
+        use gstreamer_video::VideoFormat;
+
+        let theme = Theme::default();
+        let layout = FrameLayout { width: 800, height: 480, stride: 3200, format: VideoFormat::Bgra };
+        // A status bar takes the top 40 rows
+        let ctx = RenderContext { layout, safe_area: Insets { top: 40, ..Insets::default() }, theme: &theme, clock: &SystemClock };
+        let pip = |width, height, anchor| PipConfig { camera: CameraRef::Index(1), width, height, anchor, margin: 10 };
+
+        assert_eq!(pip_rect(&ctx, &pip(240, 144, Anchor::BottomRight), &[]), Ok(Rectangle::new(Point::new(550, 326), Size::new(240, 144))));
+        assert!(pip_rect(&ctx, &pip(400, 220, Anchor::TopLeft), &[]).is_ok());
+        let err = pip_rect(&ctx, &pip(400, 240, Anchor::TopLeft), &[]).unwrap_err();
+        assert!(err.contains("half the safe area (400x220)"), "{}", err);
+
+        // A long name wraps onto two lines spanning the bottom of the screen
+        let name = "Front Door ".repeat(8);
+        let overlays = vec![("the name".to_string(), anchored_text_rect(&ctx, &name, &FONT_10X20, Anchor::BottomLeft))];
+        let err = pip_rect(&ctx, &pip(240, 144, Anchor::BottomRight), &overlays).unwrap_err();
+        assert!(err.contains("would cover the name"), "{}", err);
+        assert!(pip_rect(&ctx, &pip(240, 144, Anchor::TopRight), &overlays).is_ok());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::framebuffer::{FrameLayout, Framebuffer};
+use crate::overlay::{self, RenderContext};
+use crate::pipeline::ConnectionState;
+use crate::CameraConfig;
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{Circle, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    text::{Baseline, Text},
+};
+use gstreamer_video::VideoFormat;
+use std::time::{Duration, Instant};
+
+const TILE_GAP: u32 = 10;
+const TILE_PADDING: u32 = 6; // between the tile edge and its thumbnail and text
+const TILE_BACKGROUND: Rgb888 = Rgb888::new(32, 32, 32);
+const STATE_DOT: u32 = 10;
+
+// Dashboard of one tile per camera: latest thumbnail, name, and connection
+// state or how long ago the camera was last seen. Only the shown camera is
+// connected, so the other tiles keep what was captured while they were.
+pub(crate) struct Overview {
+    area: Rectangle,
+    tiles: Vec<Rectangle>,
+    thumb: Size,
+    interval: Duration,
+    thumbnails: Vec<Option<(Vec<u8>, Instant)>>,
+    last_seen: Vec<Option<Instant>>,
+}
+
+impl Overview {
+    // Tiles fill the safe rect in a near-square grid; `area` is cleared before drawing
+    pub fn new(count: usize, ctx: &RenderContext, area: Rectangle, line_height: u32, interval: Duration) -> Self {
+        let safe = ctx.safe_rect();
+        let cols = (1..).find(|c| c * c >= count).unwrap_or(1).max(1) as u32;
+        let rows = count.div_ceil(cols as usize).max(1) as u32;
+        let width = safe.size.width.saturating_sub((cols + 1) * TILE_GAP) / cols;
+        let height = safe.size.height.saturating_sub((rows + 1) * TILE_GAP) / rows;
+
+        let tiles = (0..count as u32)
+            .map(|i| {
+                let x = safe.top_left.x + (TILE_GAP + (i % cols) * (width + TILE_GAP)) as i32;
+                let y = safe.top_left.y + (TILE_GAP + (i / cols) * (height + TILE_GAP)) as i32;
+                Rectangle::new(Point::new(x, y), Size::new(width, height))
+            })
+            .collect();
+
+        // Two text lines under the thumbnail, which keeps the video's aspect ratio
+        let max_w = width.saturating_sub(2 * TILE_PADDING);
+        let max_h = height.saturating_sub(3 * TILE_PADDING + 2 * line_height);
+        let video = ctx.size();
+        let thumb_h = max_h.min(max_w * area.size.height / video.width.max(1));
+        let thumb = Size::new(thumb_h * video.width / area.size.height.max(1), thumb_h);
+
+        Self { area, tiles, thumb, interval, thumbnails: vec![None; count], last_seen: vec![None; count] }
+    }
+
+    // Notes a frame from `camera`, keeping a downscaled copy once per interval
+    pub fn frame(&mut self, camera: usize, layout: FrameLayout, bgra: &[u8], now: Instant) {
+        self.last_seen[camera] = Some(now);
+        if self.thumbnails[camera].as_ref().is_some_and(|(_, taken)| now.saturating_duration_since(*taken) < self.interval) {
+            return;
+        }
+        // The previous thumbnail's buffer is overwritten rather than replaced
+        let mut thumbnail = self.thumbnails[camera].take().map(|(buffer, _)| buffer).unwrap_or_default();
+        downscale(layout, bgra, self.thumb, &mut thumbnail);
+        self.thumbnails[camera] = Some((thumbnail, now));
+    }
+
+    // Index of the camera whose tile contains `point`
+    pub fn hit(&self, point: Point) -> Option<usize> {
+        self.tiles.iter().position(|tile| tile.contains(point))
+    }
+
+    pub fn draw(&self, fb: &mut Framebuffer, cameras: &[CameraConfig], current: usize, state: ConnectionState, style: MonoTextStyle<'_, Rgb888>, now: Instant) {
+        self.area.into_styled(PrimitiveStyle::with_fill(Rgb888::BLACK)).draw(fb).ok();
+        let line_height = style.font.character_size.height;
+
+        for (i, (tile, camera)) in self.tiles.iter().zip(cameras).enumerate() {
+            let mut tile_style = PrimitiveStyleBuilder::new().fill_color(TILE_BACKGROUND);
+            if i == current {
+                // Border marks the camera that is open behind the overview
+                tile_style = tile_style.stroke_color(style.text_color.unwrap_or(Rgb888::WHITE)).stroke_width(2);
+            }
+            tile.into_styled(tile_style.build()).draw(fb).ok();
+
+            let inner = Point::new(tile.top_left.x + TILE_PADDING as i32, tile.top_left.y + TILE_PADDING as i32);
+            if let Some((thumbnail, _)) = &self.thumbnails[i] {
+                let layout = FrameLayout { width: self.thumb.width, height: self.thumb.height, stride: self.thumb.width * 4, format: VideoFormat::Bgra };
+                fb.blit_at(thumbnail, layout, inner);
+            }
+
+            let text_width = tile.size.width.saturating_sub(2 * TILE_PADDING);
+            let name_top = inner.y + (self.thumb.height + TILE_PADDING) as i32;
+            let name = overlay::wrap_text(&camera.name, style.font, text_width).into_iter().next().unwrap_or_default();
+            Text::with_baseline(&name, Point::new(inner.x, name_top), style, Baseline::Top).draw(fb).ok();
+
+            let status_top = name_top + line_height as i32;
+            let (color, status) = if i == current {
+                let label = match state {
+                    ConnectionState::Live => "Live",
+                    ConnectionState::Connecting => "Connecting",
+                    ConnectionState::Reconnecting => "Reconnecting",
+                };
+                (overlay::state_color(state), label.to_string())
+            } else {
+                let seen = self.last_seen[i].map(|t| format!("seen {} ago", age(now.saturating_duration_since(t))));
+                (Rgb888::new(128, 128, 128), seen.unwrap_or_else(|| "not seen yet".to_string()))
+            };
+            let dot_center = Point::new(inner.x + STATE_DOT as i32 / 2, status_top + line_height as i32 / 2);
+            Circle::with_center(dot_center, STATE_DOT).into_styled(PrimitiveStyle::with_fill(color)).draw(fb).ok();
+            let status_left = inner.x + (STATE_DOT + TILE_PADDING) as i32;
+            let status_width = text_width.saturating_sub(STATE_DOT + TILE_PADDING);
+            let status = overlay::wrap_text(&status, style.font, status_width).into_iter().next().unwrap_or_default();
+            Text::with_baseline(&status, Point::new(status_left, status_top), style, Baseline::Top).draw(fb).ok();
+        }
+    }
+}
+
+// Nearest-neighbour scale of a BGRA frame to `size`, tightly packed into `out`
+fn downscale(layout: FrameLayout, bgra: &[u8], size: Size, out: &mut Vec<u8>) {
+    out.clear();
+    for ty in 0..size.height {
+        let row = (ty * layout.height / size.height.max(1)) as usize * layout.stride as usize;
+        for tx in 0..size.width {
+            let src = row + (tx * layout.width / size.width.max(1)) as usize * 4;
+            out.extend_from_slice(bgra.get(src..src + 4).unwrap_or(&[0, 0, 0, 255]));
+        }
+    }
+}
+
+// Short human age, e.g. 45s, 12m, 3h
+fn age(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h", s / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::config::{Insets, Theme};
+
+    #[test]
+    fn lays_out_a_near_square_grid_and_hit_tests_tiles() {
+        let theme = Theme::default();
+        let layout = FrameLayout { width: 800, height: 480, stride: 3200, format: VideoFormat::Bgra };
+        let ctx = RenderContext { layout, safe_area: Insets::default(), theme: &theme, clock: &SystemClock };
+        let area = Rectangle::new(Point::zero(), Size::new(800, 480));
+
+        // Five cameras: three columns, two rows
+        let overview = Overview::new(5, &ctx, area, 20, Duration::from_secs(10));
+        assert_eq!(overview.tiles.len(), 5);
+        assert_eq!(overview.tiles[0].size, Size::new(253, 225));
+        assert_eq!(overview.tiles[3].top_left, Point::new(10, 245));
+        assert_eq!(overview.hit(Point::new(400, 100)), Some(1));
+        assert_eq!(overview.hit(Point::new(700, 400)), None);
+        // The thumbnail keeps the 5:3 video shape
+        assert_eq!(overview.thumb, Size::new(240, 144));
+    }
+}
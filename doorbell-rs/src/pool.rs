@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Reusable byte buffers for frame copies handed to background work, such as
+// encoding a snapshot, so those paths don't allocate a fresh frame each time.
+// Keeps at most `capacity` idle buffers; cloning shares the same pool.
+#[derive(Clone)]
+pub(crate) struct BufferPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    idle: Mutex<Vec<Vec<u8>>>,
+    capacity: usize,
+    allocations: AtomicU64,
+    reuses: AtomicU64,
+}
+
+impl BufferPool {
+    pub fn new(capacity: usize) -> Self {
+        let inner = PoolInner { idle: Mutex::new(Vec::new()), capacity, allocations: AtomicU64::new(0), reuses: AtomicU64::new(0) };
+        Self { inner: Arc::new(inner) }
+    }
+
+    // Empty buffer with room for `len` bytes, an idle one if there is any
+    pub fn take(&self, len: usize) -> Vec<u8> {
+        let idle = self.inner.idle.lock().ok().and_then(|mut idle| idle.pop());
+        match idle {
+            Some(mut buffer) => {
+                self.inner.reuses.fetch_add(1, Ordering::Relaxed);
+                buffer.clear();
+                buffer.reserve(len);
+                buffer
+            }
+            None => {
+                self.inner.allocations.fetch_add(1, Ordering::Relaxed);
+                Vec::with_capacity(len)
+            }
+        }
+    }
+
+    // Returns a buffer for reuse; dropped instead if the pool is full
+    pub fn give(&self, buffer: Vec<u8>) {
+        if let Ok(mut idle) = self.inner.idle.lock() {
+            if idle.len() < self.inner.capacity {
+                idle.push(buffer);
+            }
+        }
+    }
+
+    // Buffers allocated because none was idle, and buffers reused
+    pub fn counts(&self) -> (u64, u64) {
+        (self.inner.allocations.load(Ordering::Relaxed), self.inner.reuses.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_stay_bounded_by_buffers_in_flight() {
+        let pool = BufferPool::new(2);
+        for _ in 0..100 {
+            let (a, b) = (pool.take(1024), pool.take(1024));
+            pool.give(a);
+            pool.give(b);
+        }
+        assert_eq!(pool.counts(), (2, 198));
+
+        // A third buffer in flight allocates once; the pool keeps only two of them
+        let held: Vec<_> = (0..3).map(|_| pool.take(1024)).collect();
+        held.into_iter().for_each(|buffer| pool.give(buffer));
+        assert_eq!(pool.counts(), (3, 200));
+        assert_eq!(pool.inner.idle.lock().unwrap().len(), 2);
+    }
+}
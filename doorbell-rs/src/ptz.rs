@@ -0,0 +1,192 @@
+use crate::config::{CameraConfig, PtzConfig};
+use std::fs::File;
+use std::io::Read;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const PTZ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Sends an ONVIF PTZ GotoPreset for `camera` on a background thread, so a slow
+// or unreachable camera never stalls the display. Failures are only logged.
+pub(crate) fn goto_preset(camera: &CameraConfig, ptz: &PtzConfig, preset: usize) {
+    let Some(preset) = ptz.presets.get(preset) else { return };
+    let (username, password) = credentials(camera, ptz);
+    let body = envelope(&ptz.profile_token, &preset.token, username.as_deref().zip(password.as_deref()));
+    let (camera, service, name) = (camera.name.clone(), ptz.service_url.clone(), preset.name.clone());
+
+    println!("PTZ: {} going to preset {}", camera, name);
+    thread::spawn(move || {
+        let result = ureq::post(&service)
+            .timeout(PTZ_TIMEOUT)
+            .set("Content-Type", "application/soap+xml; charset=utf-8")
+            .send_string(&body);
+        if let Err(e) = result {
+            eprintln!("PTZ: {} preset {} failed: {}", camera, name, e);
+        }
+    });
+}
+
+// Explicit PTZ credentials, else the user:pass of the camera's RTSP URL
+fn credentials(camera: &CameraConfig, ptz: &PtzConfig) -> (Option<String>, Option<String>) {
+    let from_url = camera.url.split_once("://")
+        .and_then(|(_, rest)| rest.split('/').next())
+        .and_then(|authority| authority.rsplit_once('@'))
+        .and_then(|(userinfo, _)| userinfo.split_once(':'))
+        .map(|(user, pass)| (user.to_string(), pass.to_string()));
+    let username = ptz.username.clone().or_else(|| from_url.as_ref().map(|(u, _)| u.clone()));
+    let password = ptz.password.clone().or_else(|| from_url.map(|(_, p)| p));
+    (username, password)
+}
+
+fn envelope(profile: &str, preset: &str, credentials: Option<(&str, &str)>) -> String {
+    let header = match credentials {
+        Some((username, password)) => security_header(username, password, &nonce(), &utc_timestamp()),
+        None => String::new(),
+    };
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">"#,
+            "<s:Header>{}</s:Header>",
+            r#"<s:Body><GotoPreset xmlns="http://www.onvif.org/ver20/ptz/wsdl">"#,
+            "<ProfileToken>{}</ProfileToken><PresetToken>{}</PresetToken>",
+            "</GotoPreset></s:Body></s:Envelope>"
+        ),
+        header,
+        xml_escape(profile),
+        xml_escape(preset)
+    )
+}
+
+// WS-Security UsernameToken with PasswordDigest = Base64(SHA-1(nonce + created + password))
+fn security_header(username: &str, password: &str, nonce: &[u8], created: &str) -> String {
+    let mut digest_input = nonce.to_vec();
+    digest_input.extend_from_slice(created.as_bytes());
+    digest_input.extend_from_slice(password.as_bytes());
+    format!(
+        concat!(
+            r#"<Security s:mustUnderstand="1" xmlns="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd">"#,
+            "<UsernameToken><Username>{}</Username>",
+            r#"<Password Type="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordDigest">{}</Password>"#,
+            r#"<Nonce EncodingType="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-soap-message-security-1.0#Base64Binary">{}</Nonce>"#,
+            r#"<Created xmlns="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd">{}</Created>"#,
+            "</UsernameToken></Security>"
+        ),
+        xml_escape(username),
+        base64(&sha1(&digest_input)),
+        base64(nonce),
+        created
+    )
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn nonce() -> Vec<u8> {
+    let mut bytes = [0u8; 16];
+    if File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut bytes)).is_err() {
+        // Only has to be unique per request, not secret
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        bytes = nanos.to_le_bytes();
+    }
+    bytes.to_vec()
+}
+
+// Current UTC time as xsd:dateTime, e.g. 2024-05-01T12:00:00Z
+fn utc_timestamp() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::gmtime_r(&secs, &mut tm) };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec
+    )
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut out = [0u8; 20];
+    for (chunk, value) in out.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&value.to_be_bytes());
+    }
+    out
+}
+
+pub(crate) fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn sha1_and_base64_match_known_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        let long = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        assert_eq!(hex(&sha1(long)), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn password_digest_covers_nonce_created_and_password() {
+        let header = security_header("admin", "secret", b"0123456789abcdef", "2024-05-01T12:00:00Z");
+        let expected = base64(&sha1(b"0123456789abcdef2024-05-01T12:00:00Zsecret"));
+        assert!(header.contains(&format!("PasswordDigest\">{}</Password>", expected)), "{}", header);
+        assert!(header.contains("<Username>admin</Username>"));
+        assert!(!header.contains("secret"));
+    }
+}
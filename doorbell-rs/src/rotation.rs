@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+// Time since the current camera was shown. Every switch restarts it, whatever
+// caused the switch, so manual and automatic switches get the same full dwell.
+pub(crate) struct DwellTimer {
+    shown_at: Instant,
+}
+
+impl DwellTimer {
+    pub fn new(now: Instant) -> Self {
+        Self { shown_at: now }
+    }
+
+    pub fn restart(&mut self, now: Instant) {
+        self.shown_at = now;
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.shown_at)
+    }
+
+    pub fn expired(&self, now: Instant, dwell: Duration) -> bool {
+        self.elapsed(now) >= dwell
+    }
+
+    // How far through `dwell` the current camera is, 0.0-1.0
+    pub fn progress(&self, now: Instant, dwell: Duration) -> f32 {
+        (self.elapsed(now).as_secs_f32() / dwell.as_secs_f32().max(1.0)).min(1.0)
+    }
+}
+
+// Next camera after `current` (or before it, going back) that `keep` accepts,
+// wrapping around; `current` itself when no other camera qualifies
+pub(crate) fn neighbour(count: usize, current: usize, forward: bool, keep: impl Fn(usize) -> bool) -> usize {
+    (1..count)
+        .map(|step| if forward { (current + step) % count } else { (current + count - step) % count })
+        .find(|&i| keep(i))
+        .unwrap_or(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DWELL: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn neighbour_skips_cameras_and_wraps() {
+        let yard = |i: usize| [false, true, false, true].get(i).copied().unwrap_or(false);
+        assert_eq!(neighbour(4, 1, true, yard), 3);
+        assert_eq!(neighbour(4, 3, true, yard), 1);
+        assert_eq!(neighbour(4, 1, false, yard), 3);
+        // From outside the selection, to the nearest camera in it
+        assert_eq!(neighbour(4, 0, true, yard), 1);
+        assert_eq!(neighbour(4, 2, true, |_| false), 2);
+        assert_eq!(neighbour(1, 0, true, |_| true), 0);
+    }
+
+    #[test]
+    fn expires_exactly_at_the_dwell() {
+        let start = Instant::now();
+        let timer = DwellTimer::new(start);
+        assert!(!timer.expired(start + DWELL - Duration::from_millis(1), DWELL));
+        assert!(timer.expired(start + DWELL, DWELL));
+    }
+
+    #[test]
+    fn switch_at_the_boundary_gets_a_full_dwell() {
+        let start = Instant::now();
+        let mut timer = DwellTimer::new(start);
+
+        // Manual switch just before the auto-cycle would have fired
+        let switched = start + DWELL - Duration::from_millis(1);
+        timer.restart(switched);
+        assert!(!timer.expired(start + DWELL, DWELL));
+        assert!(!timer.expired(switched + DWELL - Duration::from_millis(1), DWELL));
+        assert!(timer.expired(switched + DWELL, DWELL));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::config::BurstConfig;
+use crate::framebuffer::FrameLayout;
+use crate::pool::BufferPool;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Stills taken from the live samples after a doorbell press. Files share the
+// event ID, e.g. doorbell-1700000000-01.png, doorbell-1700000000-02.png, ...
+pub(crate) struct Burst {
+    event_id: String,
+    camera: usize,
+    taken: u32,
+    next_at: Instant,
+}
+
+impl Burst {
+    pub fn new(event_id: String, camera: usize, now: Instant) -> Self {
+        Self { event_id, camera, taken: 0, next_at: now }
+    }
+
+    pub fn camera(&self) -> usize {
+        self.camera
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        now >= self.next_at
+    }
+
+    // Saves one frame in the background, converting into a buffer from `pool`
+    // that goes back once the file is written; true once the burst is complete
+    pub fn capture(&mut self, config: &BurstConfig, layout: FrameLayout, bgra: &[u8], pool: &BufferPool, now: Instant) -> bool {
+        self.taken += 1;
+        self.next_at = now + Duration::from_millis(config.interval_ms);
+
+        let path = PathBuf::from(&config.dir).join(format!("{}-{:02}.{}", self.event_id, self.taken, config.format.extension()));
+        let mut rgb = pool.take(layout.width as usize * layout.height as usize * 3);
+        to_rgb(layout, bgra, &mut rgb);
+        let pool = pool.clone();
+        thread::spawn(move || {
+            if let Some(dir) = path.parent() {
+                if let Err(e) = fs::create_dir_all(dir) {
+                    eprintln!("Snapshot: Could not create {}: {}", dir.display(), e);
+                    return;
+                }
+            }
+            let Some(img) = image::RgbImage::from_raw(layout.width, layout.height, rgb) else {
+                eprintln!("Snapshot: Frame too small for {}x{}", layout.width, layout.height);
+                return;
+            };
+            match img.save(&path) {
+                Ok(()) => println!("Snapshot: Saved {}", path.display()),
+                Err(e) => eprintln!("Snapshot: Could not save {}: {}", path.display(), e),
+            }
+            pool.give(img.into_raw());
+        });
+        self.taken >= config.count
+    }
+}
+
+// Packs stride-padded BGRA rows into tightly packed RGB, appended to `rgb`
+fn to_rgb(layout: FrameLayout, bgra: &[u8], rgb: &mut Vec<u8>) {
+    for row in bgra.chunks(layout.stride as usize).take(layout.height as usize) {
+        for px in row[..layout.width as usize * 4].chunks_exact(4) {
+            rgb.extend_from_slice(&[px[2], px[1], px[0]]);
+        }
+    }
+}
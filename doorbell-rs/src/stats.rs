@@ -0,0 +1,203 @@
+use gstreamer::ClockTime;
+use std::time::{Duration, Instant};
+
+// Counters shared by the heartbeat log and any future status reporting
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub frames: u64,
+    pub reconnects: u64,
+    pub slow_frames: u64,
+    // Worst times since the last heartbeat: blit copy, and how long a sample was held
+    pub copy_max: Duration,
+    pub hold_max: Duration,
+    // Frame buffers allocated and reused by the buffer pool, since startup
+    pub buffer_allocations: u64,
+    pub buffer_reuses: u64,
+    // Current camera's picture has stopped changing
+    pub frozen: bool,
+}
+
+impl Stats {
+    pub fn record_timing(&mut self, copy: Duration, hold: Duration) {
+        self.copy_max = self.copy_max.max(copy);
+        self.hold_max = self.hold_max.max(hold);
+    }
+}
+
+// Logs frames whose copy or hold time exceeds a threshold, at most once per second
+pub struct FrameGuard {
+    threshold: Duration,
+    last_warning: Option<Instant>,
+}
+
+impl FrameGuard {
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold, last_warning: None }
+    }
+
+    pub fn check(&mut self, stats: &mut Stats, camera: &str, copy: Duration, hold: Duration) {
+        if copy <= self.threshold && hold <= self.threshold {
+            return;
+        }
+        stats.slow_frames += 1;
+        if self.last_warning.is_some_and(|t| t.elapsed() < Duration::from_secs(1)) {
+            return;
+        }
+        eprintln!(
+            "Frame: {} slow frame, copy {:.1} ms, sample held {:.1} ms (threshold {} ms)",
+            camera,
+            copy.as_secs_f64() * 1000.0,
+            hold.as_secs_f64() * 1000.0,
+            self.threshold.as_millis()
+        );
+        self.last_warning = Some(Instant::now());
+    }
+}
+
+// Periodic one-line summary so unattended displays can be checked from logs
+pub struct Heartbeat {
+    interval: Duration,
+    last: Instant,
+    last_frames: u64,
+}
+
+impl Heartbeat {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last: Instant::now(), last_frames: 0 }
+    }
+
+    pub fn tick(&mut self, stats: &mut Stats, camera: &str) {
+        let elapsed = self.last.elapsed();
+        if elapsed < self.interval {
+            return;
+        }
+        let fps = (stats.frames - self.last_frames) as f64 / elapsed.as_secs_f64();
+        println!(
+            "Heartbeat: camera={} fps={:.1} frames={} reconnects={} copy_max_ms={:.1} hold_max_ms={:.1} slow_frames={} buffer_allocs={} buffer_reuses={} frozen={}",
+            camera,
+            fps,
+            stats.frames,
+            stats.reconnects,
+            stats.copy_max.as_secs_f64() * 1000.0,
+            stats.hold_max.as_secs_f64() * 1000.0,
+            stats.slow_frames,
+            stats.buffer_allocations,
+            stats.buffer_reuses,
+            stats.frozen
+        );
+        self.last = Instant::now();
+        self.last_frames = stats.frames;
+        stats.copy_max = Duration::ZERO;
+        stats.hold_max = Duration::ZERO;
+    }
+}
+
+// Periodic latency line: what the pipeline reports next to the worst measured frame delay
+pub struct LatencyReport {
+    interval: Duration,
+    last: Instant,
+    max_delay: Option<ClockTime>,
+}
+
+impl LatencyReport {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last: Instant::now(), max_delay: None }
+    }
+
+    pub fn record(&mut self, delay: ClockTime) {
+        self.max_delay = Some(self.max_delay.map_or(delay, |d| d.max(delay)));
+    }
+
+    pub fn due(&self) -> bool {
+        self.last.elapsed() >= self.interval
+    }
+
+    pub fn log(&mut self, camera: &str, reported: Option<(ClockTime, Option<ClockTime>)>) {
+        let ms = |t: Option<ClockTime>| t.map_or("?".to_string(), |t| t.mseconds().to_string());
+        println!(
+            "Latency: camera={} pipeline_min_ms={} pipeline_max_ms={} frame_max_ms={}",
+            camera,
+            ms(reported.map(|(min, _)| min)),
+            ms(reported.and_then(|(_, max)| max)),
+            ms(self.max_delay)
+        );
+        self.last = Instant::now();
+        self.max_delay = None;
+    }
+}
+
+// Per-frame timings for --bench, summarised once `frames` have been drawn.
+// FPS counts from the first frame, so pipeline startup is left out.
+pub struct Bench {
+    frames: u64,
+    first_frame: Option<Instant>,
+    pull: Vec<Duration>,
+    blit: Vec<Duration>,
+    overlay: Vec<Duration>,
+}
+
+impl Bench {
+    pub fn new(frames: u64) -> Self {
+        let capacity = frames as usize;
+        Self {
+            frames,
+            first_frame: None,
+            pull: Vec::with_capacity(capacity),
+            blit: Vec::with_capacity(capacity),
+            overlay: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn record(&mut self, pull: Duration, blit: Duration, overlay: Duration) {
+        self.first_frame.get_or_insert_with(Instant::now);
+        self.pull.push(pull);
+        self.blit.push(blit);
+        self.overlay.push(overlay);
+    }
+
+    pub fn done(&self) -> bool {
+        self.pull.len() as u64 >= self.frames
+    }
+
+    pub fn report(&mut self) {
+        let elapsed = self.first_frame.map_or(Duration::ZERO, |t| t.elapsed());
+        let fps = self.pull.len().saturating_sub(1) as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        println!("Bench: {} frames, {:.1} fps", self.pull.len(), fps);
+        for (name, times) in [("pull", &mut self.pull), ("blit", &mut self.blit), ("overlay", &mut self.overlay)] {
+            let summary = summarize(times);
+            println!(
+                "Bench: {} avg_ms={:.2} p50_ms={:.2} p95_ms={:.2} p99_ms={:.2} max_ms={:.2}",
+                name, summary[0], summary[1], summary[2], summary[3], summary[4]
+            );
+        }
+    }
+}
+
+// Average, p50, p95, p99 and max in milliseconds; sorts `times`
+fn summarize(times: &mut [Duration]) -> [f64; 5] {
+    if times.is_empty() {
+        return [0.0; 5];
+    }
+    times.sort_unstable();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    // Nearest rank: the smallest time at or above that share of frames
+    let percentile = |p: usize| ms(times[(times.len() * p).div_ceil(100).max(1) - 1]);
+    let average = times.iter().map(|&t| ms(t)).sum::<f64>() / times.len() as f64;
+    [average, percentile(50), percentile(95), percentile(99), ms(times[times.len() - 1])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_with_nearest_rank_percentiles() {
+        let mut times: Vec<_> = (1..=100).rev().map(Duration::from_millis).collect();
+        let [average, p50, p95, p99, max] = summarize(&mut times);
+        assert!((average - 50.5).abs() < 1e-9);
+        assert_eq!((p50, p95, p99, max), (50.0, 95.0, 99.0, 100.0));
+
+        assert_eq!(summarize(&mut [Duration::from_millis(4)]), [4.0; 5]);
+        assert_eq!(summarize(&mut []), [0.0; 5]);
+    }
+}
@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// How soon a worker notices it should stop, at most, when it only sleeps
+const STOP_POLL: Duration = Duration::from_millis(200);
+
+// Background thread that runs while `active` is set. Dropping the handle clears
+// it and joins the thread, including while unwinding from a panic, so whatever
+// the thread holds (a grabbed device, a bound socket) is released before the
+// display that started it returns, and a later run can take it again.
+pub(crate) struct Worker {
+    active: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    pub fn spawn(body: impl FnOnce(&AtomicBool) + Send + 'static) -> Self {
+        let active = Arc::new(AtomicBool::new(true));
+        let running = active.clone();
+        let handle = thread::spawn(move || body(&running));
+        Self { active, handle: Some(handle) }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            // A panic in the thread was already reported by the panic hook
+            handle.join().ok();
+        }
+    }
+}
+
+// Sleeps for `duration` in short steps, returning early once `active` clears
+pub(crate) fn sleep_while(active: &AtomicBool, duration: Duration) {
+    let mut left = duration;
+    while !left.is_zero() && active.load(Ordering::Relaxed) {
+        let step = left.min(STOP_POLL);
+        thread::sleep(step);
+        left -= step;
+    }
+}
@@ -0,0 +1,78 @@
+// End-to-end check of the decode path against a local RTP/H.264 stream.
+// Needs x264enc, avdec_h264 and the RTP/UDP plugins: `cargo test -- --ignored`
+use doorbell_rs::framebuffer::FrameLayout;
+use doorbell_rs::pipeline::{PipelineShare, VideoPipeline};
+use doorbell_rs::Config;
+use gstreamer::prelude::*;
+use gstreamer::ClockTime;
+use gstreamer_video::VideoFormat;
+use std::collections::HashSet;
+
+const EXPECTED: FrameLayout = FrameLayout { width: 800, height: 480, stride: 800 * 4, format: VideoFormat::Bgra };
+
+#[test]
+#[ignore = "requires x264enc, avdec_h264 and the RTP plugins"]
+fn decodes_local_rtp_stream_to_bgra_frames() {
+    gstreamer::init().unwrap();
+    let port = 40000 + std::process::id() % 10000;
+
+    let sender = gstreamer::parse::launch(&format!(
+        "videotestsrc is-live=true ! video/x-raw,width=640,height=360,framerate=15/1 ! x264enc tune=zerolatency key-int-max=15 ! rtph264pay config-interval=1 pt=96 ! udpsink host=127.0.0.1 port={}",
+        port
+    ))
+    .unwrap();
+    sender.set_state(gstreamer::State::Playing).unwrap();
+
+    // Same tail as the built-in pipeline, with a UDP source and software decode
+    let receiver = format!(
+        "udpsrc port={} caps=\"application/x-rtp,media=video,encoding-name=H264,clock-rate=90000,payload=96\" ! rtph264depay ! h264parse ! avdec_h264 ! videoconvert ! videoscale ! video/x-raw,format=BGRA,width={},height={} ! appsink name=sink drop=true max-buffers=1",
+        port, EXPECTED.width, EXPECTED.height
+    );
+    let video = VideoPipeline::launch("test", &receiver).unwrap();
+
+    for _ in 0..5 {
+        let sample = video.sink.try_pull_sample(ClockTime::from_seconds(10)).expect("no sample within 10s");
+        let layout = FrameLayout::from_caps(sample.caps().unwrap()).expect("sample caps are not video");
+        let len = sample.buffer().unwrap().size();
+        assert_eq!(layout.mismatch(&EXPECTED, len), None);
+    }
+
+    video.stop().unwrap();
+    sender.set_state(gstreamer::State::Null).unwrap();
+}
+
+fn assert_delivers_frames(video: &VideoPipeline) {
+    let sample = video.sink.try_pull_sample(ClockTime::from_seconds(10)).expect("no sample within 10s");
+    let layout = FrameLayout::from_caps(sample.caps().unwrap()).expect("sample caps are not video");
+    assert_eq!(layout.mismatch(&EXPECTED, sample.buffer().unwrap().size()), None);
+}
+
+#[test]
+#[ignore = "requires the videotestsrc, videoconvert and videoscale plugins"]
+fn displays_showing_the_same_camera_share_one_pipeline() {
+    gstreamer::init().unwrap();
+    let config: Config = serde_json::from_value(serde_json::json!({
+        "cameras": [{
+            "name": "Test",
+            "pipeline": "videotestsrc is-live=true ! videoconvert ! videoscale ! video/x-raw,format=BGRA,width=800,height=480 ! appsink name=sink drop=true max-buffers=1"
+        }]
+    }))
+    .unwrap();
+    let camera = &config.cameras[0];
+    let share = PipelineShare::new(HashSet::from(["Test".to_string()]));
+
+    let first = share.open(&config, camera, false).unwrap();
+    let second = share.open(&config, camera, false).unwrap();
+    assert_eq!(first.pipeline, second.pipeline);
+    assert_ne!(first.sink, second.sink);
+    assert_delivers_frames(&first);
+    assert_delivers_frames(&second);
+
+    // The display still on the pipeline keeps getting frames
+    first.stop().unwrap();
+    assert_delivers_frames(&second);
+    assert_delivers_frames(&second);
+
+    second.stop().unwrap();
+    assert_eq!(second.pipeline.current_state(), gstreamer::State::Null);
+}